@@ -0,0 +1,52 @@
+//! Times the lexer over generated expressions of doubling size. The time per
+//! byte should stay roughly flat as the input grows.
+//!
+//! Run with `cargo bench --bench lexer`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use awesome_calc::lexer::Lexer;
+
+const ITERATIONS: u32 = 20;
+
+fn expression(bytes: usize) -> String {
+    let mut s = String::with_capacity(bytes + 32);
+    let mut n = 0u64;
+
+    while s.len() < bytes {
+        s.push_str(&format!("({n}.5 + {} * 3) / 7 - ", n * 31 % 1000));
+        n += 1;
+    }
+    s.push('1');
+
+    s
+}
+
+fn time_lexing(input: &str) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for tok in Lexer::new(black_box(input)) {
+            black_box(tok.expect("generated input is valid"));
+        }
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    println!("{:>10} {:>14} {:>12}", "bytes", "time", "ns/byte");
+
+    for shift in 0..8 {
+        let input = expression(1024 << shift);
+        let elapsed = time_lexing(&input);
+
+        println!(
+            "{:>10} {:>14?} {:>12.2}",
+            input.len(),
+            elapsed,
+            elapsed.as_nanos() as f64 / input.len() as f64
+        );
+    }
+}
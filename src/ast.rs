@@ -1,47 +1,298 @@
-use std::fmt;
+use std::{cmp::Ordering, fmt, rc::Rc};
 
+use crate::{
+    datetime::{Date, DateTime},
+    span::Span,
+};
+
+/// An expression, with its number literals already converted to the
+/// numeric type `N`.
 #[derive(Debug)]
-pub enum Ast {
-    Number(f64),
+pub enum Ast<N = f64> {
+    Number {
+        value: N,
+        span: Span,
+    },
+    /// An imaginary literal such as `4i`, with `value` its coefficient.
+    Imaginary {
+        value: N,
+        span: Span,
+    },
+    Bool {
+        value: bool,
+        span: Span,
+    },
+    Date {
+        value: Date,
+        span: Span,
+    },
+    DateTime {
+        value: DateTime,
+        span: Span,
+    },
+    Variable {
+        name: String,
+        span: Span,
+    },
+    /// A list literal, as in `[1, 2, 3]`.
+    List {
+        elements: Vec<Ast<N>>,
+        span: Span,
+    },
+    /// `value[index]`, an element of a list, counting from zero.
+    Index {
+        value: Box<Ast<N>>,
+        index: Box<Ast<N>>,
+        span: Span,
+    },
+    /// `from..to`, the list of numbers from `from` up to and including `to`
+    /// in steps of one.
+    Range {
+        from: Box<Ast<N>>,
+        to: Box<Ast<N>>,
+        span: Span,
+    },
+    Assign {
+        name: String,
+        value: Box<Ast<N>>,
+        span: Span,
+    },
+    /// `name(params) = body`, which defines a function.
+    FunctionDef {
+        name: String,
+        params: Vec<String>,
+        body: Rc<Ast<N>>,
+        span: Span,
+    },
+    /// `(params) -> body`, an anonymous function, whose parentheses may be
+    /// left out when it has a single parameter, as in `x -> x^2`.
+    Lambda {
+        params: Vec<String>,
+        body: Rc<Ast<N>>,
+        span: Span,
+    },
+    Call {
+        name: String,
+        args: Vec<Ast<N>>,
+        span: Span,
+    },
+    /// `function(args)`, a call of the function that another call returns,
+    /// as in `adder(2)(3)`.
+    Apply {
+        function: Box<Ast<N>>,
+        args: Vec<Ast<N>>,
+        span: Span,
+    },
     UnaryExpr {
         op: Op,
-        rhs: Box<Ast>,
+        rhs: Box<Ast<N>>,
+        span: Span,
     },
     BinaryExpr {
-        lhs: Box<Ast>,
+        lhs: Box<Ast<N>>,
         op: Op,
-        rhs: Box<Ast>,
+        rhs: Box<Ast<N>>,
+        span: Span,
+    },
+    Compare {
+        lhs: Box<Ast<N>>,
+        op: Comparison,
+        rhs: Box<Ast<N>>,
+        span: Span,
     },
+    /// `lhs and rhs` or `lhs or rhs`, which only evaluates `rhs` if `lhs`
+    /// does not decide the result.
+    Logical {
+        lhs: Box<Ast<N>>,
+        op: Logic,
+        rhs: Box<Ast<N>>,
+        span: Span,
+    },
+    Not {
+        rhs: Box<Ast<N>>,
+        span: Span,
+    },
+    /// `if condition then value else otherwise`, or equally
+    /// `condition ? value : otherwise`.
+    If {
+        condition: Box<Ast<N>>,
+        value: Box<Ast<N>>,
+        otherwise: Box<Ast<N>>,
+        span: Span,
+    },
+    /// Quantities written one after another, as in `3 h 20 min`, which is
+    /// their sum.
+    Compound {
+        parts: Vec<Ast<N>>,
+        span: Span,
+    },
+    /// `value to target`, which expresses `value` in the unit `target`.
+    Convert {
+        value: Box<Ast<N>>,
+        target: Box<Ast<N>>,
+        span: Span,
+    },
+}
+
+impl<N> Ast<N> {
+    /// The region of the input this node was parsed from.
+    pub fn span(&self) -> Span {
+        match *self {
+            Ast::Number { span, .. }
+            | Ast::Imaginary { span, .. }
+            | Ast::Bool { span, .. }
+            | Ast::Date { span, .. }
+            | Ast::DateTime { span, .. }
+            | Ast::Variable { span, .. }
+            | Ast::List { span, .. }
+            | Ast::Index { span, .. }
+            | Ast::Range { span, .. }
+            | Ast::Assign { span, .. }
+            | Ast::FunctionDef { span, .. }
+            | Ast::Lambda { span, .. }
+            | Ast::Call { span, .. }
+            | Ast::Apply { span, .. }
+            | Ast::UnaryExpr { span, .. }
+            | Ast::BinaryExpr { span, .. }
+            | Ast::Compare { span, .. }
+            | Ast::Logical { span, .. }
+            | Ast::Not { span, .. }
+            | Ast::If { span, .. }
+            | Ast::Compound { span, .. }
+            | Ast::Convert { span, .. } => span,
+        }
+    }
 }
 
-impl fmt::Display for Ast {
+impl<N: fmt::Display> fmt::Display for Ast<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}",
             match *self {
-                Ast::Number(ref n) => n.to_string(),
-                Ast::UnaryExpr { ref op, ref rhs } => {
+                Ast::Number { ref value, .. } => value.to_string(),
+                Ast::Imaginary { ref value, .. } => format!("{value}i"),
+                Ast::Bool { value, .. } => value.to_string(),
+                Ast::Date { ref value, .. } => value.to_string(),
+                Ast::DateTime { ref value, .. } => value.to_string(),
+                Ast::Variable { ref name, .. } => name.to_owned(),
+                Ast::List { ref elements, .. } => format!(
+                    "[{}]",
+                    elements
+                        .iter()
+                        .map(|element| element.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                Ast::Index {
+                    ref value,
+                    ref index,
+                    ..
+                } => format!("{value}[{index}]"),
+                Ast::Range {
+                    ref from, ref to, ..
+                } => format!("{from}..{to}"),
+                Ast::Assign {
+                    ref name,
+                    ref value,
+                    ..
+                } => format!("{name}={value}"),
+                Ast::FunctionDef {
+                    ref name,
+                    ref params,
+                    ref body,
+                    ..
+                } => format!("{name}({})={body}", params.join(",")),
+                Ast::Lambda {
+                    ref params,
+                    ref body,
+                    ..
+                } => match params.as_slice() {
+                    [param] => format!("{param}->{body}"),
+                    _ => format!("({})->{body}", params.join(",")),
+                },
+                Ast::Call {
+                    ref name, ref args, ..
+                } => format!(
+                    "{name}({})",
+                    args.iter()
+                        .map(|arg| arg.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                Ast::Apply {
+                    ref function,
+                    ref args,
+                    ..
+                } => format!(
+                    "{function}({})",
+                    args.iter()
+                        .map(|arg| arg.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                Ast::UnaryExpr {
+                    ref op, ref rhs, ..
+                } => {
                     format!("{op}{rhs}")
                 }
                 Ast::BinaryExpr {
                     ref lhs,
                     ref op,
                     ref rhs,
+                    ..
                 } => {
                     format!("{lhs}{op}{rhs}")
                 }
+                Ast::Compare {
+                    ref lhs,
+                    ref op,
+                    ref rhs,
+                    ..
+                } => format!("{lhs}{op}{rhs}"),
+                Ast::Logical {
+                    ref lhs,
+                    ref op,
+                    ref rhs,
+                    ..
+                } => format!("{lhs} {op} {rhs}"),
+                Ast::Not { ref rhs, .. } => format!("not {rhs}"),
+                Ast::If {
+                    ref condition,
+                    ref value,
+                    ref otherwise,
+                    ..
+                } => format!("if {condition} then {value} else {otherwise}"),
+                Ast::Compound { ref parts, .. } => parts
+                    .iter()
+                    .map(|part| part.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                Ast::Convert {
+                    ref value,
+                    ref target,
+                    ..
+                } => format!("{value} to {target}"),
             }
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Op {
     Add,
     Sub,
     Mul,
     Div,
+    Pow,
+    /// `%`: the remainder of truncating division, which takes the sign of
+    /// the dividend, so `-7 % 3 == -1`.
+    Rem,
+    /// `mod`: the remainder of flooring division, which takes the sign of
+    /// the divisor, so `-7 mod 3 == 2` and `7 mod -3 == -2`.
+    Mod,
+    /// `//`: division rounded towards negative infinity, so `-7 // 2 == -4`.
+    FloorDiv,
 }
 
 impl fmt::Display for Op {
@@ -54,6 +305,79 @@ impl fmt::Display for Op {
                 Op::Sub => "-",
                 Op::Mul => "*",
                 Op::Div => "/",
+                Op::Pow => "^",
+                Op::Rem => "%",
+                Op::Mod => " mod ",
+                Op::FloorDiv => "//",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    /// Whether the comparison only asks if its operands are equal.
+    pub fn is_equality(self) -> bool {
+        matches!(self, Comparison::Eq | Comparison::Ne)
+    }
+
+    /// Whether the comparison holds for operands that are ordered as
+    /// `ordering`, or are unordered, as NaN is with everything.
+    pub fn holds(self, ordering: Option<Ordering>) -> bool {
+        match ordering {
+            None => self == Comparison::Ne,
+            Some(ordering) => match self {
+                Comparison::Eq => ordering.is_eq(),
+                Comparison::Ne => ordering.is_ne(),
+                Comparison::Lt => ordering.is_lt(),
+                Comparison::Le => ordering.is_le(),
+                Comparison::Gt => ordering.is_gt(),
+                Comparison::Ge => ordering.is_ge(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Comparison::Eq => "==",
+                Comparison::Ne => "!=",
+                Comparison::Lt => "<",
+                Comparison::Le => "<=",
+                Comparison::Gt => ">",
+                Comparison::Ge => ">=",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Logic {
+    And,
+    Or,
+}
+
+impl fmt::Display for Logic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Logic::And => "and",
+                Logic::Or => "or",
             }
         )
     }
@@ -0,0 +1,359 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// The furthest from 1970 a date may be, about 270,000 years either way, so
+/// that arithmetic on the seconds since then cannot overflow.
+const MAX_DAYS: i64 = 100_000_000;
+
+/// The number of days from 1970-01-01 to the given date of the proleptic
+/// Gregorian calendar, after Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let (month, day) = (i64::from(month), i64::from(day));
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses `s` as a number of exactly `digits` digits.
+fn parse_digits(s: &str, digits: usize) -> Option<u32> {
+    (s.len() == digits && s.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| s.parse().ok())
+        .flatten()
+}
+
+/// The time since 1970 by the system clock.
+fn seconds_now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    }
+}
+
+/// A day of the proleptic Gregorian calendar, such as `2026-10-18`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// Days since 1970-01-01.
+    days: i64,
+}
+
+impl Date {
+    /// The date, or `None` if there is no such day, as with February 30th.
+    pub fn from_ymd(year: i64, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+        Self::from_days(days_from_civil(year, month, day))
+    }
+
+    /// The date `days` days after 1970-01-01, or `None` if it is out of
+    /// range.
+    pub fn from_days(days: i64) -> Option<Self> {
+        (days.abs() <= MAX_DAYS).then_some(Self { days })
+    }
+
+    /// Today's date in UTC, by the system clock.
+    pub fn today() -> Self {
+        Self {
+            days: seconds_now().div_euclid(SECONDS_PER_DAY),
+        }
+    }
+
+    pub fn ymd(&self) -> (i64, u32, u32) {
+        civil_from_days(self.days)
+    }
+
+    /// Days since 1970-01-01.
+    pub fn days(&self) -> i64 {
+        self.days
+    }
+
+    pub fn add_days(&self, days: i64) -> Option<Self> {
+        Self::from_days(self.days.checked_add(days)?)
+    }
+
+    /// The start of the day in UTC.
+    pub fn to_datetime(self) -> DateTime {
+        DateTime {
+            seconds: self.days * SECONDS_PER_DAY,
+            offset: Offset::UTC,
+        }
+    }
+}
+
+/// Parses a date written as `YYYY-MM-DD`.
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut fields = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (
+            fields.next().and_then(|y| parse_digits(y, 4)),
+            fields.next().and_then(|m| parse_digits(m, 2)),
+            fields.next().and_then(|d| parse_digits(d, 2)),
+        ) else {
+            return Err(format!("'{s}' is not written as YYYY-MM-DD"));
+        };
+        let year = i64::from(year);
+
+        if !(1..=12).contains(&month) {
+            return Err(format!("there is no month {month}"));
+        }
+        Self::from_ymd(year, month, day)
+            .ok_or_else(|| format!("there is no day {day} in {year}-{month:02}"))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.ymd();
+        write!(f, "{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// A fixed offset from UTC, such as `+02:00`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Offset {
+    seconds: i32,
+}
+
+impl Offset {
+    pub const UTC: Offset = Offset { seconds: 0 };
+
+    /// The offset of `hours` and `minutes` east of UTC, or `None` if it is
+    /// more than a day.
+    pub fn new(east: bool, hours: u32, minutes: u32) -> Option<Self> {
+        if hours >= 24 || minutes >= 60 {
+            return None;
+        }
+        let seconds = (hours * 3600 + minutes * 60) as i32;
+        Some(Self {
+            seconds: if east { seconds } else { -seconds },
+        })
+    }
+
+    pub fn seconds(&self) -> i32 {
+        self.seconds
+    }
+}
+
+/// Parses `Z`, or an offset written as `+HH:MM` or `-HH:MM`.
+impl FromStr for Offset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s == "Z" {
+            return Ok(Self::UTC);
+        }
+        let east = s.starts_with('+');
+        let parsed = s
+            .strip_prefix(['+', '-'])
+            .and_then(|hm| hm.split_once(':'))
+            .and_then(|(h, m)| Some((parse_digits(h, 2)?, parse_digits(m, 2)?)));
+
+        match parsed {
+            Some((hours, minutes)) => Self::new(east, hours, minutes)
+                .ok_or_else(|| format!("the offset {s} is out of range")),
+            None => Err(format!("'{s}' is not written as Z, +HH:MM or -HH:MM")),
+        }
+    }
+}
+
+/// Displays UTC as `Z`, and other offsets as in `+02:00`.
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.seconds == 0 {
+            return write!(f, "Z");
+        }
+        let sign = if self.seconds < 0 { '-' } else { '+' };
+        let minutes = self.seconds.unsigned_abs() / 60;
+        write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+/// An instant, to the second, shown in the time of some offset from UTC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    /// Seconds since 1970-01-01T00:00:00Z.
+    seconds: i64,
+    offset: Offset,
+}
+
+impl DateTime {
+    /// The instant at `seconds` past midnight of `date`, in the time of
+    /// `offset`.
+    pub fn new(date: Date, seconds: i64, offset: Offset) -> Option<Self> {
+        let seconds = date.days * SECONDS_PER_DAY + seconds - i64::from(offset.seconds);
+        Self::from_timestamp(seconds, offset)
+    }
+
+    /// The instant `seconds` after 1970-01-01T00:00:00Z, or `None` if it is
+    /// out of range.
+    pub fn from_timestamp(seconds: i64, offset: Offset) -> Option<Self> {
+        (seconds.div_euclid(SECONDS_PER_DAY).abs() <= MAX_DAYS).then_some(Self { seconds, offset })
+    }
+
+    /// The current time in UTC, by the system clock.
+    pub fn now() -> Self {
+        Self {
+            seconds: seconds_now(),
+            offset: Offset::UTC,
+        }
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z.
+    pub fn timestamp(&self) -> i64 {
+        self.seconds
+    }
+
+    pub fn offset(&self) -> Offset {
+        self.offset
+    }
+
+    pub fn add_seconds(&self, seconds: i64) -> Option<Self> {
+        Self::from_timestamp(self.seconds.checked_add(seconds)?, self.offset)
+    }
+
+    /// The date and the seconds past midnight in the time of the offset.
+    fn local(&self) -> (Date, i64) {
+        let local = self.seconds + i64::from(self.offset.seconds);
+        let date = Date {
+            days: local.div_euclid(SECONDS_PER_DAY),
+        };
+        (date, local.rem_euclid(SECONDS_PER_DAY))
+    }
+}
+
+/// Parses a datetime written as `YYYY-MM-DDTHH:MM`, with optional seconds
+/// and an optional offset, as in `2026-10-18T14:30:00+02:00`. Datetimes
+/// without an offset are in UTC.
+impl FromStr for DateTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let Some((date, time)) = s.split_once('T') else {
+            return Err(format!("'{s}' has no time"));
+        };
+        let date = date.parse::<Date>()?;
+
+        let split = time.find(['Z', '+', '-']).unwrap_or(time.len());
+        let (time, offset) = time.split_at(split);
+        let offset = if offset.is_empty() {
+            Offset::UTC
+        } else {
+            offset.parse()?
+        };
+
+        let mut fields = time.split(':');
+        let fields = (
+            fields.next().and_then(|h| parse_digits(h, 2)),
+            fields.next().and_then(|m| parse_digits(m, 2)),
+            fields.next().map(|s| parse_digits(s, 2)).unwrap_or(Some(0)),
+            fields.next(),
+        );
+        let (Some(hours), Some(minutes), Some(seconds), None) = fields else {
+            return Err(format!("'{time}' is not written as HH:MM or HH:MM:SS"));
+        };
+        if hours >= 24 || minutes >= 60 || seconds >= 60 {
+            return Err(format!("there is no time {time}"));
+        }
+
+        let seconds = i64::from(hours * 3600 + minutes * 60 + seconds);
+        Self::new(date, seconds, offset).ok_or_else(|| format!("{s} is out of range"))
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (date, seconds) = self.local();
+        write!(
+            f,
+            "{date}T{:02}:{:02}:{:02}{}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.offset
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(Some(0), Date::from_ymd(1970, 1, 1).map(|d| d.days()));
+        assert_eq!(Some(-1), Date::from_ymd(1969, 12, 31).map(|d| d.days()));
+        for (year, month, day) in [(2026, 10, 18), (2000, 2, 29), (1600, 3, 1), (-44, 3, 15)] {
+            let date = Date::from_ymd(year, month, day).unwrap();
+            assert_eq!((year, month, day), date.ymd());
+        }
+        assert_eq!(None, Date::from_ymd(2026, 2, 29));
+        assert_eq!(None, Date::from_ymd(1900, 2, 29));
+        assert_eq!(None, Date::from_ymd(2026, 13, 1));
+
+        let date = "2026-10-18".parse::<Date>().unwrap();
+        assert_eq!("2027-01-16", date.add_days(90).unwrap().to_string());
+        assert!("2026-02-30".parse::<Date>().is_err());
+        assert!("2026-1-30".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn test_datetimes() {
+        let utc = "2026-10-18T14:30".parse::<DateTime>().unwrap();
+        assert_eq!("2026-10-18T14:30:00Z", utc.to_string());
+
+        let local = "2026-10-18T23:30:15+02:00".parse::<DateTime>().unwrap();
+        assert_eq!("2026-10-18T23:30:15+02:00", local.to_string());
+        assert_eq!(
+            "2026-10-18T21:30:15Z",
+            DateTime::from_timestamp(local.timestamp(), Offset::UTC)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "2026-10-19T00:30:15+02:00",
+            local.add_seconds(3600).unwrap().to_string()
+        );
+
+        let west = "2026-10-18T02:00-05:30".parse::<DateTime>().unwrap();
+        assert_eq!(utc.timestamp() - 7 * 3600, west.timestamp());
+        assert_eq!("-05:30", west.offset().to_string());
+
+        assert!("2026-10-18T24:00".parse::<DateTime>().is_err());
+        assert!("2026-10-18T12:00+25:00".parse::<DateTime>().is_err());
+        assert!("2026-10-18T12".parse::<DateTime>().is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt::{self, Write};
+
+use crate::span::Span;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Note => "note",
+            }
+        )
+    }
+}
+
+/// A message attached to a region of the input. The primary label marks the
+/// offending span with carets, secondary labels with dashes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    pub primary: bool,
+}
+
+/// A message about the input, with optional labelled spans and help notes,
+/// that can be rendered against the source it refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn note(message: impl Into<String>) -> Self {
+        Self::new(Severity::Note, message)
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    pub fn with_secondary_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Renders the diagnostic against `source`, quoting every line that has a
+    /// label on it and underlining the labelled spans:
+    ///
+    /// ```text
+    /// error: expected ')', but found end of input
+    ///  --> 1:7
+    ///   |
+    /// 1 | (1 + 2
+    ///   | -     ^ expected ')'
+    ///   | |
+    ///   | unclosed '(' opened here
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}: {}", self.severity, self.message);
+
+        let mut lines = self.labels.iter().map(|l| l.span.line).collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.dedup();
+
+        let gutter = lines.last().map_or(0, |l| l.to_string().len());
+        let pad = " ".repeat(gutter);
+
+        if let Some(label) = self
+            .labels
+            .iter()
+            .find(|l| l.primary)
+            .or(self.labels.first())
+        {
+            let _ = writeln!(out, "{pad}--> {}", label.span);
+            let _ = writeln!(out, "{pad} |");
+        }
+
+        for line in lines {
+            let text = source.lines().nth(line.saturating_sub(1)).unwrap_or("");
+            let line_len = text.chars().count();
+
+            let mut labels = self
+                .labels
+                .iter()
+                .filter(|l| l.span.line == line)
+                .collect::<Vec<_>>();
+            labels.sort_by_key(|l| l.span.column);
+
+            let _ = writeln!(out, "{line:>gutter$} | {text}");
+
+            let mut underline = String::new();
+            for label in &labels {
+                let column = label.span.column.saturating_sub(1);
+                let width = span_width(source, label.span)
+                    .clamp(1, (line_len + 1).saturating_sub(column).max(1));
+                let marker = if label.primary { '^' } else { '-' };
+
+                pad_to(&mut underline, column);
+                underline.truncate(column);
+                underline.extend(std::iter::repeat_n(marker, width));
+            }
+            if let Some(last) = labels.last() {
+                if !last.message.is_empty() {
+                    underline.push(' ');
+                    underline.push_str(&last.message);
+                }
+            }
+            let _ = writeln!(out, "{pad} | {underline}");
+
+            // Messages of all but the rightmost label hang below their markers.
+            for i in (0..labels.len().saturating_sub(1)).rev() {
+                if labels[i].message.is_empty() {
+                    continue;
+                }
+
+                let mut connectors = String::new();
+                for label in &labels[..=i] {
+                    pad_to(&mut connectors, label.span.column.saturating_sub(1));
+                    connectors.push('|');
+                }
+                let _ = writeln!(out, "{pad} | {connectors}");
+
+                let mut message = String::new();
+                for label in &labels[..i] {
+                    pad_to(&mut message, label.span.column.saturating_sub(1));
+                    message.push('|');
+                }
+                pad_to(&mut message, labels[i].span.column.saturating_sub(1));
+                message.push_str(&labels[i].message);
+                let _ = writeln!(out, "{pad} | {message}");
+            }
+        }
+
+        for help in &self.help {
+            let _ = writeln!(out, "{pad} = help: {help}");
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// The number of characters `span` covers on its first line.
+fn span_width(source: &str, span: Span) -> usize {
+    source
+        .get(span.start..span.end)
+        .map_or(0, |s| s.chars().take_while(|&c| c != '\n').count())
+}
+
+fn pad_to(s: &mut String, column: usize) {
+    let len = s.chars().count();
+    if len < column {
+        s.extend(std::iter::repeat_n(' ', column - len));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = "((1+2) * (3 4";
+        let diagnostic = Diagnostic::error("expected ')', but found number '4'")
+            .with_label(Span::new(12, 13, 1, 13), "expected ')'")
+            .with_secondary_label(Span::new(9, 10, 1, 10), "unclosed '(' opened here")
+            .with_help("add a ')'");
+        let expected = "\
+error: expected ')', but found number '4'
+ --> 1:13
+  |
+1 | ((1+2) * (3 4
+  |          -  ^ expected ')'
+  |          |
+  |          unclosed '(' opened here
+  = help: add a ')'
+";
+
+        assert_eq!(expected, diagnostic.render(source));
+    }
+}
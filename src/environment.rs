@@ -0,0 +1,143 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::value::Value;
+
+/// The parameters of a call, inside the scope that the called function was
+/// created in, if it is a lambda created in another call.
+#[derive(Debug)]
+pub struct Scope<N = f64> {
+    bindings: HashMap<String, Value<N>>,
+    parent: Option<Rc<Scope<N>>>,
+}
+
+impl<N> Scope<N> {
+    pub fn new(
+        bindings: impl IntoIterator<Item = (String, Value<N>)>,
+        parent: Option<Rc<Scope<N>>>,
+    ) -> Self {
+        Self {
+            bindings: bindings.into_iter().collect(),
+            parent,
+        }
+    }
+
+    /// The value of `name` in this scope or, failing that, in the scopes
+    /// enclosing it.
+    pub fn get(&self, name: &str) -> Option<&Value<N>> {
+        self.bindings
+            .get(name)
+            .or_else(|| self.parent.as_deref()?.get(name))
+    }
+
+    /// The bindings of this scope, without those of the scopes enclosing it.
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Value<N>)> {
+        self.bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn parent(&self) -> Option<&Rc<Scope<N>>> {
+        self.parent.as_ref()
+    }
+}
+
+/// The variable bindings that persist between evaluations, and the scopes
+/// of the function calls under way.
+#[derive(Debug, Clone)]
+pub struct Environment<N = f64> {
+    bindings: HashMap<String, Value<N>>,
+    /// The scope of each call under way, innermost last. Only the innermost
+    /// call's is visible, so that a function sees its own parameters, those
+    /// of the calls it was created in, and the global variables, but not
+    /// those of its caller.
+    scopes: Vec<Rc<Scope<N>>>,
+}
+
+impl Environment {
+    /// Creates an environment of `f64` values. Environments of other
+    /// numeric types are created with [`Environment::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<N> Environment<N> {
+    pub fn get(&self, name: &str) -> Option<&Value<N>> {
+        self.scopes
+            .last()
+            .and_then(|scope| scope.get(name))
+            .or_else(|| self.bindings.get(name))
+    }
+
+    /// Binds `name` to `value` globally, returning the value it was
+    /// previously bound to.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Value<N>>) -> Option<Value<N>> {
+        self.bindings.insert(name.into(), value.into())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value<N>> {
+        self.bindings.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Enters a scope in which `bindings` hide those of `parent` and the
+    /// global variables of the same names, until the matching
+    /// [`Environment::pop_scope`].
+    pub fn push_scope(
+        &mut self,
+        bindings: impl IntoIterator<Item = (String, Value<N>)>,
+        parent: Option<Rc<Scope<N>>>,
+    ) {
+        self.scopes.push(Rc::new(Scope::new(bindings, parent)));
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// The innermost scope together with those enclosing it, for a function
+    /// created in it to keep.
+    pub fn capture(&self) -> Option<Rc<Scope<N>>> {
+        self.scopes.last().cloned()
+    }
+
+    /// How many scopes have been entered and not left.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// The global variables.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value<N>)> {
+        self.bindings
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+impl<N> Default for Environment<N> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+}
+
+impl<N, S: Into<String>, V: Into<Value<N>>> FromIterator<(S, V)> for Environment<N> {
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Self {
+        let mut env = Environment::default();
+        env.extend(iter);
+        env
+    }
+}
+
+impl<N, S: Into<String>, V: Into<Value<N>>> Extend<(S, V)> for Environment<N> {
+    fn extend<I: IntoIterator<Item = (S, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.set(name, value);
+        }
+    }
+}
@@ -1,430 +1,4663 @@
-use crate::ast::{Ast, Op};
+mod constants;
+mod functions;
+mod higher_order;
+mod lists;
+mod matrices;
 
-pub struct Evaluator;
+pub use constants::{Constant, Constants, UnitPowers};
+pub use functions::{Arity, Function, Functions};
+
+use higher_order::HIGHER_ORDER;
+use lists::LISTS;
+use matrices::MATRICES;
+
+use std::{fmt, rc::Rc};
+
+use crate::{
+    ast::{Ast, Comparison, Logic, Op},
+    datetime::{Date, DateTime},
+    diagnostic::Diagnostic,
+    environment::Environment,
+    number::{self, complex::Complex, Numeric},
+    span::Span,
+    units::{Dimension, NamedUnit, Quantity, Unit, Units},
+    value::{Matrix, UserFunction, Value},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("undefined variable: '{name}'")]
+    UndefinedVariable { name: String, span: Span },
+
+    #[error("cannot assign to constant '{name}'")]
+    AssignToConstant { name: String, span: Span },
+
+    #[error("unknown function: '{name}'")]
+    UnknownFunction { name: String, span: Span },
+
+    #[error("division by zero")]
+    DivisionByZero { span: Span },
+
+    #[error("{message}")]
+    Domain { message: String, span: Span },
+
+    #[error("overflow")]
+    Overflow { span: Span },
+
+    #[error("{source}")]
+    Arithmetic { source: number::Error, span: Span },
+
+    #[error("unsupported operator: '{op}'")]
+    UnsupportedOperator { op: Op, span: Span },
+
+    #[error("{name} is not defined for complex numbers")]
+    ComplexArgument { name: String, span: Span },
+
+    #[error("incompatible units: {lhs} and {rhs}")]
+    IncompatibleUnits {
+        lhs: String,
+        rhs: String,
+        span: Span,
+    },
+
+    #[error("{message}")]
+    InvalidUnits { message: String, span: Span },
+
+    #[error("{message}")]
+    TypeMismatch { message: String, span: Span },
+
+    #[error("{message}")]
+    InvalidArgument { message: String, span: Span },
+
+    #[error("cannot mix currencies: {lhs} and {rhs}")]
+    MixedCurrencies {
+        lhs: String,
+        rhs: String,
+        span: Span,
+    },
+
+    #[error("too much recursion in '{name}': more than {limit} nested calls")]
+    RecursionLimit {
+        name: String,
+        limit: usize,
+        span: Span,
+    },
+
+    #[error("{name} needs lists of the same length, got {lhs} and {rhs}")]
+    ShapeMismatch {
+        name: String,
+        lhs: usize,
+        rhs: usize,
+        span: Span,
+    },
+
+    #[error("{name} needs {requirement}, got {lhs} and {rhs}")]
+    IncompatibleShapes {
+        name: String,
+        requirement: &'static str,
+        lhs: String,
+        rhs: String,
+        span: Span,
+    },
+
+    #[error("{name} is undefined for a singular matrix")]
+    SingularMatrix { name: String, span: Span },
+
+    #[error("index {index} is out of range for a list of length {len}")]
+    IndexOutOfRange { index: i64, len: usize, span: Span },
+
+    #[error("{name} expects {expected}, got {got}")]
+    WrongArgumentCount {
+        name: String,
+        expected: Arity,
+        got: usize,
+        span: Span,
+    },
+}
+
+impl Error {
+    pub fn span(&self) -> Span {
+        match *self {
+            Error::UndefinedVariable { span, .. }
+            | Error::AssignToConstant { span, .. }
+            | Error::UnknownFunction { span, .. }
+            | Error::DivisionByZero { span }
+            | Error::Domain { span, .. }
+            | Error::Overflow { span }
+            | Error::Arithmetic { span, .. }
+            | Error::UnsupportedOperator { span, .. }
+            | Error::ComplexArgument { span, .. }
+            | Error::IncompatibleUnits { span, .. }
+            | Error::InvalidUnits { span, .. }
+            | Error::TypeMismatch { span, .. }
+            | Error::InvalidArgument { span, .. }
+            | Error::MixedCurrencies { span, .. }
+            | Error::RecursionLimit { span, .. }
+            | Error::ShapeMismatch { span, .. }
+            | Error::IncompatibleShapes { span, .. }
+            | Error::SingularMatrix { span, .. }
+            | Error::IndexOutOfRange { span, .. }
+            | Error::WrongArgumentCount { span, .. } => span,
+        }
+    }
+
+    /// The span, for moving an error raised in the body of a function to
+    /// the call of it, since the body was parsed from another input.
+    fn span_mut(&mut self) -> &mut Span {
+        match self {
+            Error::UndefinedVariable { span, .. }
+            | Error::AssignToConstant { span, .. }
+            | Error::UnknownFunction { span, .. }
+            | Error::DivisionByZero { span }
+            | Error::Domain { span, .. }
+            | Error::Overflow { span }
+            | Error::Arithmetic { span, .. }
+            | Error::UnsupportedOperator { span, .. }
+            | Error::ComplexArgument { span, .. }
+            | Error::IncompatibleUnits { span, .. }
+            | Error::InvalidUnits { span, .. }
+            | Error::TypeMismatch { span, .. }
+            | Error::InvalidArgument { span, .. }
+            | Error::MixedCurrencies { span, .. }
+            | Error::RecursionLimit { span, .. }
+            | Error::ShapeMismatch { span, .. }
+            | Error::IncompatibleShapes { span, .. }
+            | Error::SingularMatrix { span, .. }
+            | Error::IndexOutOfRange { span, .. }
+            | Error::WrongArgumentCount { span, .. } => span,
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::UndefinedVariable { name, span } => {
+                Diagnostic::error(format!("undefined variable '{name}'"))
+                    .with_label(*span, "not defined")
+                    .with_help(format!("assign it a value first, as in `{name} = 1`"))
+            }
+            Error::AssignToConstant { name, span } => {
+                Diagnostic::error(format!("cannot assign to constant '{name}'"))
+                    .with_label(*span, "constants are read-only")
+                    .with_help("pick a different name for the variable")
+            }
+            Error::UnknownFunction { name, span } => {
+                Diagnostic::error(format!("unknown function '{name}'"))
+                    .with_label(*span, "no function by this name")
+            }
+            Error::DivisionByZero { span } => Diagnostic::error("division by zero")
+                .with_label(*span, "the divisor is zero")
+                .with_help("turn off strict mode to get IEEE 754 infinities instead"),
+            Error::Domain { message, span } => Diagnostic::error(message)
+                .with_label(*span, "the result is not a number")
+                .with_help("turn off strict mode to get NaN instead"),
+            Error::Overflow { span } => Diagnostic::error("overflow")
+                .with_label(*span, "the result is too large to represent")
+                .with_help("turn off strict mode to get IEEE 754 infinities instead"),
+            Error::Arithmetic { source, span } => match source {
+                number::Error::DivisionByZero => {
+                    Diagnostic::error("division by zero").with_label(*span, "the divisor is zero")
+                }
+                number::Error::Overflow => Diagnostic::error("overflow")
+                    .with_label(*span, "the result is out of range for this type of number"),
+                number::Error::Inexact => Diagnostic::error("inexact result")
+                    .with_label(*span, "this type of number cannot represent the result"),
+            },
+            Error::UnsupportedOperator { op, span } => {
+                Diagnostic::error(format!("unsupported operator '{op}'"))
+                    .with_label(*span, "cannot be used here")
+            }
+            Error::ComplexArgument { name, span } => {
+                Diagnostic::error(format!("{name} is not defined for complex numbers"))
+                    .with_label(*span, "called with a complex argument")
+                    .with_help("re, im, abs and arg turn a complex number into a real one")
+            }
+            Error::IncompatibleUnits { lhs, rhs, span } => {
+                Diagnostic::error(format!("incompatible units: {lhs} and {rhs}"))
+                    .with_label(*span, "these measure different things")
+            }
+            Error::InvalidUnits { message, span } => {
+                Diagnostic::error(message).with_label(*span, "")
+            }
+            Error::TypeMismatch { message, span } | Error::InvalidArgument { message, span } => {
+                Diagnostic::error(message).with_label(*span, "")
+            }
+            Error::MixedCurrencies { lhs, rhs, span } => {
+                Diagnostic::error(format!("cannot mix currencies: {lhs} and {rhs}"))
+                    .with_label(*span, "these are in different currencies")
+                    .with_help(format!("convert one of them first, as in `... to {rhs}`"))
+            }
+            Error::RecursionLimit { name, limit, span } => {
+                Diagnostic::error(format!("too much recursion in '{name}'"))
+                    .with_label(*span, format!("more than {limit} calls deep"))
+                    .with_help("check that the recursion reaches a case that does not recurse")
+            }
+            Error::ShapeMismatch {
+                name,
+                lhs,
+                rhs,
+                span,
+            } => Diagnostic::error(format!("{name} needs lists of the same length"))
+                .with_label(*span, format!("lengths {lhs} and {rhs}")),
+            Error::IncompatibleShapes {
+                name,
+                requirement,
+                lhs,
+                rhs,
+                span,
+            } => Diagnostic::error(format!("{name} needs {requirement}"))
+                .with_label(*span, format!("{lhs} and {rhs}")),
+            Error::SingularMatrix { name, span } => {
+                Diagnostic::error(format!("{name} is undefined for a singular matrix"))
+                    .with_label(*span, "this matrix has no inverse")
+                    .with_help("its determinant is zero")
+            }
+            Error::IndexOutOfRange { index, len, span } => {
+                Diagnostic::error(format!("index {index} is out of range"))
+                    .with_label(
+                        *span,
+                        format!(
+                            "the list has {len} element{}",
+                            if *len == 1 { "" } else { "s" }
+                        ),
+                    )
+                    .with_help("indices count from 0")
+            }
+            Error::WrongArgumentCount {
+                name,
+                expected,
+                got,
+                span,
+            } => Diagnostic::error(self.to_string())
+                .with_label(*span, format!("called with {got}"))
+                .with_help(format!("{name} takes {expected}")),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Settings that control how expressions are evaluated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EvalOptions {
+    /// Report division by zero, results that are not a number and results
+    /// too large to represent as errors. When off, these evaluate to the
+    /// IEEE 754 infinities and NaN instead.
+    pub strict: bool,
+    /// How deeply calls to user-defined functions may nest, which stops
+    /// runaway recursion before it overflows the stack.
+    pub max_depth: usize,
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            max_depth: 200,
+        }
+    }
+}
+
+/// Evaluates expressions against an environment of variables that persists
+/// from one evaluation to the next, doing arithmetic in the numeric type `N`.
+#[derive(Debug)]
+pub struct Evaluator<N: Numeric = f64> {
+    env: Environment<N>,
+    constants: Constants,
+    functions: Functions,
+    units: Units,
+    options: EvalOptions,
+    context: N::Context,
+    /// Notes about the evaluations since they were last taken.
+    diagnostics: Vec<Diagnostic>,
+}
 
 impl Evaluator {
-    pub fn eval(ast: Ast) -> f64 {
+    /// Creates an evaluator that works in `f64`. Evaluators for other
+    /// numeric types are created with [`Evaluator::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: EvalOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+}
+
+impl<N: Numeric> Default for Evaluator<N> {
+    fn default() -> Self {
+        Self {
+            env: Environment::default(),
+            constants: Constants::default(),
+            functions: Functions::default(),
+            units: Units::default(),
+            options: EvalOptions::default(),
+            context: N::Context::default(),
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl<N: Numeric> Evaluator<N> {
+    /// Creates an evaluator with `env` preloaded, so that its bindings can be
+    /// referred to by the expressions it evaluates.
+    pub fn with_environment(env: Environment<N>) -> Self {
+        Self {
+            env,
+            ..Self::default()
+        }
+    }
+
+    pub fn options(&self) -> &EvalOptions {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut EvalOptions {
+        &mut self.options
+    }
+
+    pub fn environment(&self) -> &Environment<N> {
+        &self.env
+    }
+
+    pub fn environment_mut(&mut self) -> &mut Environment<N> {
+        &mut self.env
+    }
+
+    pub fn constants(&self) -> &Constants {
+        &self.constants
+    }
+
+    /// The constants table, for instance to opt in to the physical constants
+    /// with [`Constants::add_physical`].
+    pub fn constants_mut(&mut self) -> &mut Constants {
+        &mut self.constants
+    }
+
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+
+    /// The function registry, for registering functions beyond the built-in
+    /// ones.
+    pub fn functions_mut(&mut self) -> &mut Functions {
+        &mut self.functions
+    }
+
+    pub fn units(&self) -> &Units {
+        &self.units
+    }
+
+    /// The units database, for defining units beyond the built-in ones.
+    pub fn units_mut(&mut self) -> &mut Units {
+        &mut self.units
+    }
+
+    /// The settings of `N`'s arithmetic, such as a decimal precision.
+    pub fn context(&self) -> &N::Context {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut N::Context {
+        &mut self.context
+    }
+
+    /// Takes the notes that evaluating has produced since they were last
+    /// taken, such as the date of the exchange rates a conversion used.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn eval(&mut self, ast: &Ast<N>) -> Result<Value<N>> {
         match ast {
-            Ast::Number(n) => n,
-            Ast::UnaryExpr { op, rhs } => {
-                let e = Evaluator::eval(*rhs);
-                match op {
-                    Op::Add => e,
-                    Op::Sub => -e,
-                    _ => unreachable!(),
+            Ast::Number { value, .. } => Ok(Value::Number(value.clone())),
+            Ast::Imaginary { value, span } => {
+                Self::value_from_complex(Complex::new(0.0, value.to_f64()), *span)
+            }
+            Ast::Bool { value, .. } => Ok(Value::Bool(*value)),
+            Ast::Date { value, .. } => Ok(Value::Date(*value)),
+            Ast::DateTime { value, .. } => Ok(Value::DateTime(*value)),
+            Ast::Variable { name, span } => self.eval_variable(name, *span),
+            Ast::List { elements, span } => self.eval_list(elements, *span),
+            Ast::Index { value, index, span } => self.eval_index(value, index, *span),
+            Ast::Range { from, to, span } => self.eval_range(from, to, *span),
+            Ast::Assign { name, value, span } => self.assign(name, value, *span),
+            Ast::FunctionDef {
+                name,
+                params,
+                body,
+                span,
+            } => self.define(name, params, body, *span),
+            Ast::Lambda { params, body, span } => self.lambda(params, body, *span),
+            Ast::Call { name, args, span } => match self.env.get(name) {
+                Some(Value::Function(function)) => {
+                    let function = Rc::clone(function);
+                    let args = args
+                        .iter()
+                        .map(|arg| self.eval(arg))
+                        .collect::<Result<Vec<_>>>()?;
+                    self.call(name, &function, args, *span)
+                }
+                _ => self.eval_builtin(name, args, *span),
+            },
+            Ast::Apply {
+                function,
+                args,
+                span,
+            } => {
+                let callee = match self.eval(function)? {
+                    Value::Function(callee) => callee,
+                    value => {
+                        return Err(Error::TypeMismatch {
+                            message: format!("expected a function, not {}", Self::describe(&value)),
+                            span: function.span(),
+                        })
+                    }
+                };
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(&function.to_string(), &callee, args, *span)
+            }
+            Ast::UnaryExpr { op, rhs, span } => self.eval_unary(*op, rhs, *span),
+            Ast::BinaryExpr { lhs, op, rhs, span } => self.eval_binary(lhs, *op, rhs, *span),
+            Ast::Compare { lhs, op, rhs, span } => {
+                let (l, r) = (self.eval(lhs)?, self.eval(rhs)?);
+                self.compare(&l, *op, &r, *span).map(Value::Bool)
+            }
+            Ast::Logical { lhs, op, rhs, .. } => {
+                let l = self.eval_condition(lhs)?;
+                // The right hand side is only evaluated if it can change
+                // the result.
+                match (op, l) {
+                    (Logic::And, false) => Ok(Value::Bool(false)),
+                    (Logic::Or, true) => Ok(Value::Bool(true)),
+                    _ => self.eval_condition(rhs).map(Value::Bool),
+                }
+            }
+            Ast::Not { rhs, .. } => self.eval_condition(rhs).map(|b| Value::Bool(!b)),
+            Ast::If {
+                condition,
+                value,
+                otherwise,
+                ..
+            } => {
+                if self.eval_condition(condition)? {
+                    self.eval(value)
+                } else {
+                    self.eval(otherwise)
+                }
+            }
+            Ast::Compound { parts, span } => self.eval_compound(parts, *span),
+            Ast::Convert {
+                value,
+                target,
+                span,
+            } => self.eval_convert(value, target, *span),
+        }
+    }
+
+    fn eval_variable(&self, name: &str, span: Span) -> Result<Value<N>> {
+        match self.constants.get(name) {
+            Some(constant) if !self.units.contains(name) => self.constant_value(constant, span),
+            _ => match (self.env.get(name), self.units.get(name)) {
+                (Some(value), _) => Ok(value.clone()),
+                (None, Some(unit)) => {
+                    Self::value_from_quantity(Quantity::new(1.0, Unit::from(unit)), span)
+                }
+                (None, None) if name == "today" => Ok(Value::Date(Date::today())),
+                (None, None) if name == "now" => Ok(Value::DateTime(DateTime::now())),
+                // The imaginary unit, unless a variable takes its name.
+                (None, None) if matches!(name, "i" | "j") => {
+                    Self::value_from_complex(Complex::I, span)
+                }
+                (None, None) => Err(Error::UndefinedVariable {
+                    name: name.to_owned(),
+                    span,
+                }),
+            },
+        }
+    }
+
+    fn assign(&mut self, name: &str, value: &Ast<N>, span: Span) -> Result<Value<N>> {
+        if self.constants.contains(name) {
+            return Err(Error::AssignToConstant {
+                name: name.to_owned(),
+                span,
+            });
+        }
+        let value = self.eval(value)?;
+        self.warn_shadowing(name, span);
+        self.env.set(name, value.clone());
+        Ok(value)
+    }
+
+    /// Warns that a new variable `name` hides the unit or the imaginary
+    /// unit of that name, which unlike a constant it is allowed to.
+    fn warn_shadowing(&mut self, name: &str, span: Span) {
+        if self.env.contains(name) {
+            return;
+        }
+        let (hidden, kept) = if self.units.contains(name) {
+            ("a unit", "the unit")
+        } else if matches!(name, "i" | "j") {
+            ("the imaginary unit", "the imaginary unit")
+        } else {
+            return;
+        };
+        self.diagnostics.push(
+            Diagnostic::warning(format!("'{name}' shadows {hidden}"))
+                .with_label(span, format!("{name} will mean this from now on"))
+                .with_help(format!("pick another name to keep {kept}")),
+        );
+    }
+
+    /// Defines a function, binding it globally under its name.
+    fn define(
+        &mut self,
+        name: &str,
+        params: &[String],
+        body: &Rc<Ast<N>>,
+        span: Span,
+    ) -> Result<Value<N>> {
+        if self.constants.contains(name) {
+            return Err(Error::AssignToConstant {
+                name: name.to_owned(),
+                span,
+            });
+        }
+        self.check_parameters(params, span)?;
+        self.warn_shadowing(name, span);
+        if let Some(Value::Function(previous)) = self.env.get(name) {
+            self.diagnostics.push(
+                Diagnostic::warning(format!("function '{name}' is redefined"))
+                    .with_label(span, format!("this replaces {previous}")),
+            );
+        } else if self.functions.get(name).is_some()
+            || [HIGHER_ORDER, LISTS, MATRICES]
+                .iter()
+                .any(|table| Self::builtin(table, name).is_some())
+        {
+            self.diagnostics.push(
+                Diagnostic::warning(format!("'{name}' shadows a built-in function"))
+                    .with_label(span, format!("calls to {name} will use this instead"))
+                    .with_help("pick another name to keep the built-in"),
+            );
+        }
+
+        let function = Value::Function(Rc::new(UserFunction {
+            name: Some(name.to_owned()),
+            params: params.to_vec(),
+            body: Rc::clone(body),
+            captured: None,
+        }));
+        self.env.set(name, function.clone());
+        Ok(function)
+    }
+
+    /// Creates an anonymous function, which keeps the parameters of the call
+    /// it is created in.
+    fn lambda(&mut self, params: &[String], body: &Rc<Ast<N>>, span: Span) -> Result<Value<N>> {
+        self.check_parameters(params, span)?;
+        Ok(Value::Function(Rc::new(UserFunction {
+            name: None,
+            params: params.to_vec(),
+            body: Rc::clone(body),
+            captured: self.env.capture(),
+        })))
+    }
+
+    /// The arity of the built-in `name` in `table`, if it is there.
+    fn builtin(table: &[(&str, Arity)], name: &str) -> Option<Arity> {
+        table
+            .iter()
+            .find(|&&(builtin, _)| builtin == name)
+            .map(|&(_, arity)| arity)
+    }
+
+    /// Checks that no parameter is named after a constant, which would hide
+    /// it.
+    fn check_parameters(&self, params: &[String], span: Span) -> Result<()> {
+        match params.iter().find(|name| self.constants.contains(name)) {
+            Some(name) => Err(Error::AssignToConstant {
+                name: name.to_owned(),
+                span,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Calls the built-in `name`, looking in the tables of built-ins that
+    /// take other than numbers before the function registry.
+    fn eval_builtin(&mut self, name: &str, args: &[Ast<N>], span: Span) -> Result<Value<N>> {
+        if let Some(arity) = Self::builtin(HIGHER_ORDER, name) {
+            self.eval_higher_order(name, arity, args, span)
+        } else if let Some(arity) = Self::builtin(LISTS, name) {
+            self.eval_list_function(name, arity, args, span)
+        } else if let Some(arity) = Self::builtin(MATRICES, name) {
+            self.eval_matrix_function(name, arity, args, span)
+        } else {
+            self.eval_call(name, args, span)
+        }
+    }
+
+    /// Calls the built-in function `name`.
+    fn eval_call(&mut self, name: &str, args: &[Ast<N>], span: Span) -> Result<Value<N>> {
+        let function = *self
+            .functions
+            .get(name)
+            .ok_or_else(|| Error::UnknownFunction {
+                name: name.to_owned(),
+                span,
+            })?;
+        if !function.arity.accepts(args.len()) {
+            return Err(Error::WrongArgumentCount {
+                name: name.to_owned(),
+                expected: function.arity,
+                got: args.len(),
+                span,
+            });
+        }
+
+        let args = args
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let kind = args.iter().find_map(|arg| match arg {
+            Value::Date(_) | Value::DateTime(_) => Some("dates"),
+            Value::Bool(_) => Some("booleans"),
+            Value::Function(_) => Some("functions"),
+            Value::List(_) => Some("lists"),
+            Value::Matrix(_) => Some("matrices"),
+            _ => None,
+        });
+        if let Some(kind) = kind {
+            return Err(Error::TypeMismatch {
+                message: format!("{name} is not defined for {kind}"),
+                span,
+            });
+        }
+        if let Some(result) = self.call_in_n(name, &args, span)? {
+            return Ok(result);
+        }
+        let domain_message = || format!("{name} is undefined for these arguments");
+
+        if let Some(reals) = args.iter().map(Value::to_f64).collect::<Option<Vec<_>>>() {
+            let result = (function.call)(&reals);
+            let promote = result.is_nan() && reals.iter().all(|x| x.is_finite());
+            if !(promote && function.complex.is_some()) {
+                let result = self.check(&reals, result, span, domain_message)?;
+                return Self::value_from_f64(result, span);
+            }
+        }
+
+        // An argument is complex, or the function has no real result
+        // for these arguments but may have a complex one.
+        let Some(args) = args
+            .iter()
+            .map(Value::to_complex)
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Err(Error::InvalidUnits {
+                message: format!("{name} is not defined for quantities with units"),
+                span,
+            });
+        };
+        let complex = function.complex.ok_or_else(|| Error::ComplexArgument {
+            name: name.to_owned(),
+            span,
+        })?;
+        let result = complex(&args);
+        let result = self.check_complex(&args, result, span, domain_message)?;
+        Self::value_from_complex(result, span)
+    }
+
+    /// Calls the built-ins that `N` has itself, `abs`, `sign`, `floor`,
+    /// `ceil`, `round`, `min`, `max` and `sqrt`, so that exact and decimal
+    /// numbers do not lose precision in `f64`. Returns `None` for other
+    /// functions and arguments, and for results that `N` cannot hold.
+    fn call_in_n(&self, name: &str, args: &[Value<N>], span: Span) -> Result<Option<Value<N>>> {
+        let Some(numbers) = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Number(n) => Some(n),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(None);
+        };
+        let ctx = &self.context;
+        let result = match (name, numbers.as_slice()) {
+            ("abs", [x]) => x.abs(ctx),
+            ("sign", [x]) => x.signum(ctx),
+            ("floor", [x]) => x.floor(ctx),
+            ("ceil", [x]) => x.ceil(ctx),
+            ("round", [x]) => x.round(ctx),
+            ("sqrt", [x]) => x.sqrt(ctx),
+            ("min" | "max", [first, rest @ ..]) => {
+                let wanted = if name == "min" {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Greater
+                };
+                let mut best = *first;
+                for &x in rest {
+                    match x.partial_cmp(best) {
+                        Some(ordering) if ordering == wanted => best = x,
+                        Some(_) => {}
+                        // NaN, which `f64` handles.
+                        None => return Ok(None),
+                    }
                 }
+                Ok(best.clone())
+            }
+            _ => return Ok(None),
+        };
+        match result {
+            Ok(n) => Ok(Some(Value::Number(n))),
+            Err(number::Error::Inexact) => Ok(None),
+            Err(source) => Err(Error::Arithmetic { source, span }),
+        }
+    }
+
+    fn eval_unary(&mut self, op: Op, rhs: &Ast<N>, span: Span) -> Result<Value<N>> {
+        let e = self.eval(rhs)?;
+        self.unary(op, e, span)
+    }
+
+    /// Applies a sign to a number or quantity, and to each element of a
+    /// list or matrix.
+    fn unary(&self, op: Op, e: Value<N>, span: Span) -> Result<Value<N>> {
+        match (op, e) {
+            (Op::Add | Op::Sub, Value::List(list)) => list
+                .into_iter()
+                .map(|e| self.unary(op, e, span))
+                .collect::<Result<_>>()
+                .map(Value::List),
+            (Op::Add | Op::Sub, Value::Matrix(matrix)) => {
+                let elements = matrix
+                    .elements()
+                    .iter()
+                    .map(|e| self.unary(op, e.clone(), span))
+                    .collect::<Result<_>>()?;
+                Ok(Value::Matrix(Matrix::new(
+                    matrix.rows(),
+                    matrix.cols(),
+                    elements,
+                )))
+            }
+            (
+                Op::Add,
+                e @ (Value::Number(_) | Value::Float(_) | Value::Complex(_) | Value::Quantity(_)),
+            ) => Ok(e),
+            (Op::Sub, Value::Number(e)) => e
+                .neg(&self.context)
+                .map(Value::Number)
+                .map_err(|source| Error::Arithmetic { source, span }),
+            (Op::Sub, Value::Float(e)) => Ok(Value::Float(-e)),
+            (Op::Sub, Value::Complex(e)) => Ok(Value::Complex(-e)),
+            (Op::Sub, Value::Quantity(e)) => Ok(Value::Quantity(e.neg())),
+            (Op::Add | Op::Sub, e) => Err(Error::TypeMismatch {
+                message: format!(
+                    "'{}' is not defined for {}",
+                    op.to_string().trim(),
+                    Self::describe(&e)
+                ),
+                span,
+            }),
+            _ => Err(Error::UnsupportedOperator { op, span }),
+        }
+    }
+
+    fn eval_binary(&mut self, lhs: &Ast<N>, op: Op, rhs: &Ast<N>, span: Span) -> Result<Value<N>> {
+        let (l, r) = (self.eval(lhs)?, self.eval(rhs)?);
+        let result = self.binary(&l, op, &r, span)?;
+        if Self::converts_currencies(&l, op, &r) {
+            self.note_rates(span);
+        }
+        Ok(result)
+    }
+
+    /// Whether `l op r` converts between currencies, as `100 USD / 50 EUR`
+    /// does: a product or quotient in which different currencies cancel
+    /// out, leaving a number that the exchange rates decide. Lists and
+    /// matrices are checked by their elements.
+    fn converts_currencies(l: &Value<N>, op: Op, r: &Value<N>) -> bool {
+        fn units<N: Numeric>(value: &Value<N>) -> Vec<&Unit> {
+            match value {
+                Value::Quantity(q) => vec![&q.unit],
+                Value::List(list) => list.iter().flat_map(units).collect(),
+                Value::Matrix(matrix) => matrix.elements().iter().flat_map(units).collect(),
+                _ => Vec::new(),
+            }
+        }
+        let (lhs, rhs) = (units(l), units(r));
+        matches!(op, Op::Mul | Op::Div)
+            && lhs.iter().any(|a| {
+                rhs.iter().any(|b| {
+                    let unit = if op == Op::Mul { a.mul(b) } else { a.div(b) };
+                    unit.dimension().is_none() && !unit.currencies().is_empty()
+                })
+            })
+    }
+
+    /// Applies an arithmetic operator to two values. The operator applies
+    /// to lists element by element: two lists of the same length pair up
+    /// their elements, and a list and another value apply it to each
+    /// element and that value, as in `[1, 2] * 3`. Matrices have rules of
+    /// their own, in [`Evaluator::eval_matrix`].
+    fn binary(&self, l: &Value<N>, op: Op, r: &Value<N>, span: Span) -> Result<Value<N>> {
+        if matches!(l, Value::Matrix(_)) || matches!(r, Value::Matrix(_)) {
+            return self.eval_matrix(l, op, r, span);
+        }
+        let each = |pairs: &mut dyn Iterator<Item = (&Value<N>, &Value<N>)>| {
+            pairs
+                .map(|(l, r)| self.binary(l, op, r, span))
+                .collect::<Result<_>>()
+                .map(Value::List)
+        };
+        match (l, r) {
+            (Value::List(a), Value::List(b)) if a.len() != b.len() => {
+                return Err(Error::ShapeMismatch {
+                    name: format!("'{}'", op.to_string().trim()),
+                    lhs: a.len(),
+                    rhs: b.len(),
+                    span,
+                })
             }
-            Ast::BinaryExpr { lhs, op, rhs } => {
-                let l = Evaluator::eval(*lhs);
-                let r = Evaluator::eval(*rhs);
+            (Value::List(a), Value::List(b)) => return each(&mut a.iter().zip(b)),
+            (Value::List(a), _) => return each(&mut a.iter().map(|l| (l, r))),
+            (_, Value::List(b)) => return each(&mut b.iter().map(|r| (l, r))),
+            _ => {}
+        }
+        let is_other = |v: &Value<N>| matches!(v, Value::Bool(_) | Value::Function(_));
+        if is_other(l) || is_other(r) {
+            return Err(Self::mismatch(l, op, r, span));
+        }
+        let is_date = |v: &Value<N>| matches!(v, Value::Date(_) | Value::DateTime(_));
+        if is_date(l) || is_date(r) {
+            return self.eval_dates(l, op, r, span);
+        }
+        if matches!(l, Value::Quantity(_)) || matches!(r, Value::Quantity(_)) {
+            return self.eval_quantity(l, op, r, span);
+        }
 
-                match op {
-                    Op::Add => l + r,
-                    Op::Sub => l - r,
-                    Op::Mul => l * r,
-                    Op::Div => l / r,
+        if let (Value::Number(l), Value::Number(r)) = (l, r) {
+            match Self::apply(l, op, r, &self.context) {
+                // A negative float to a fractional power, which the
+                // `f64` path below turns into a complex number.
+                Ok(result)
+                    if op == Op::Pow && result.is_nan() && l.is_finite() && r.is_finite() => {}
+                Ok(result) => {
+                    return self
+                        .check_division(op, r, span)
+                        .and_then(|()| {
+                            self.check([l, r], result, span, || {
+                                format!("'{op}' is undefined for {l} and {r}")
+                            })
+                        })
+                        .map(Value::Number);
                 }
+                Err(number::Error::Inexact) if N::FLOAT_FALLBACK => {}
+                Err(source) => return Err(Error::Arithmetic { source, span }),
             }
         }
+
+        // Either operand is a float, or `N` has no result for these
+        // operands, so redo the operation in `f64`.
+        let (Some(l), Some(r)) = (l.to_f64(), r.to_f64()) else {
+            let (l, r) = (l.to_complex(), r.to_complex());
+            let (l, r) = l.zip(r).expect("quantities are handled above");
+            return self.eval_complex(l, op, r, span);
+        };
+        let result = Self::apply(&l, op, &r, &()).expect("f64 arithmetic does not fail");
+        if op == Op::Pow && result.is_nan() && l.is_finite() && r.is_finite() {
+            // A negative number to a fractional power, as in `(-8)^0.5`.
+            return self.eval_complex(Complex::from(l), op, Complex::from(r), span);
+        }
+        self.check_division(op, &r, span)?;
+        let result = self.check([&l, &r], result, span, || {
+            format!("'{op}' is undefined for {l} and {r}")
+        })?;
+        Self::value_from_f64(result, span)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{lexer::Lexer, parser::Parser};
+    fn eval_compound(&mut self, parts: &[Ast<N>], span: Span) -> Result<Value<N>> {
+        // The sum is in the unit of the last part, usually the
+        // smallest, so that `3 h 20 min` is 200 min.
+        let mut sum: Option<Quantity> = None;
+        for part in parts.iter().rev() {
+            let Value::Quantity(q) = self.eval(part)? else {
+                return Err(Error::InvalidUnits {
+                    message: "only quantities with units can be written one after \
+                              another, as in `3 h 20 min`"
+                        .to_owned(),
+                    span: part.span(),
+                });
+            };
+            sum = match sum {
+                None => Some(q),
+                Some(sum) => Some(
+                    sum.add(&q)
+                        .ok_or_else(|| Self::incompatible(&q.unit, &sum.unit, span))?,
+                ),
+            };
+        }
+        let sum = sum.expect("a compound has parts");
+        Self::value_from_quantity(sum, span)
+    }
+
+    fn eval_convert(&mut self, value: &Ast<N>, target: &Ast<N>, span: Span) -> Result<Value<N>> {
+        let value = self.eval(value)?;
+        let kind = match value {
+            Value::Date(_) | Value::DateTime(_) => Some("Dates"),
+            Value::Bool(_) => Some("Booleans"),
+            Value::Function(_) => Some("Functions"),
+            Value::List(_) => Some("Lists"),
+            Value::Matrix(_) => Some("Matrices"),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            return Err(Error::TypeMismatch {
+                message: format!("{kind} cannot be converted to units"),
+                span,
+            });
+        }
+        let unit = match self.eval(target)? {
+            Value::Quantity(q) if q.value == 1.0 => q.unit,
+            _ => {
+                return Err(Error::InvalidUnits {
+                    message: "can only convert to a unit, such as `m/s`".to_owned(),
+                    span: target.span(),
+                })
+            }
+        };
+        let quantity = value.to_quantity().ok_or_else(|| Error::InvalidUnits {
+            message: "complex numbers cannot have units".to_owned(),
+            span,
+        })?;
+
+        let result = quantity
+            .convert(&unit)
+            .ok_or_else(|| Self::incompatible(&quantity.unit, &unit, span))?;
+        if quantity.unit.currencies() != unit.currencies() {
+            self.note_rates(span);
+        }
+        Ok(Value::Quantity(result))
+    }
+
+    /// Notes the date of the exchange rates that a conversion at `span`
+    /// used, once per evaluation.
+    fn note_rates(&mut self, span: Span) {
+        let Some(date) = self.units.rates_date() else {
+            return;
+        };
+        let message = format!("exchange rates as of {date}");
+        if self.diagnostics.iter().all(|d| d.message != message) {
+            self.diagnostics
+                .push(Diagnostic::note(message).with_label(span, "converted at these rates"));
+        }
+    }
+
+    /// Calls a user-defined function by `name`, evaluating its body in a
+    /// scope of its own in which the parameters are bound to `args`.
+    fn call(
+        &mut self,
+        name: &str,
+        function: &UserFunction<N>,
+        args: Vec<Value<N>>,
+        span: Span,
+    ) -> Result<Value<N>> {
+        if args.len() != function.params.len() {
+            return Err(Error::WrongArgumentCount {
+                name: name.to_owned(),
+                expected: Arity::Exact(function.params.len()),
+                got: args.len(),
+                span,
+            });
+        }
+        if self.env.depth() >= self.options.max_depth {
+            return Err(Error::RecursionLimit {
+                name: name.to_owned(),
+                limit: self.options.max_depth,
+                span,
+            });
+        }
+
+        self.env.push_scope(
+            function.params.iter().cloned().zip(args),
+            function.captured.clone(),
+        );
+        let diagnostics = self.diagnostics.len();
+        let result = self.eval(&function.body);
+        self.env.pop_scope();
+
+        for diagnostic in &mut self.diagnostics[diagnostics..] {
+            for label in &mut diagnostic.labels {
+                label.span = span;
+            }
+        }
+        result.map_err(|mut err| {
+            *err.span_mut() = span;
+            err
+        })
+    }
+
+    fn eval_quantity(&self, l: &Value<N>, op: Op, r: &Value<N>, span: Span) -> Result<Value<N>> {
+        let (Some(l), Some(r)) = (l.to_quantity(), r.to_quantity()) else {
+            return Err(Error::InvalidUnits {
+                message: "complex numbers cannot have units".to_owned(),
+                span,
+            });
+        };
+
+        let result = match op {
+            Op::Add => l.add(&r),
+            Op::Sub => l.sub(&r),
+            Op::Mul => Some(l.mul(&r)),
+            Op::Div => {
+                if self.options.strict && r.value == 0.0 {
+                    return Err(Error::DivisionByZero { span });
+                }
+                Some(l.div(&r))
+            }
+            Op::Pow => {
+                if !r.unit.is_one() {
+                    return Err(Error::InvalidUnits {
+                        message: "exponents cannot have units".to_owned(),
+                        span,
+                    });
+                }
+                let result = l.pow(r.value).ok_or_else(|| Error::InvalidUnits {
+                    message: format!(
+                        "raising {} to the power {} leaves a fractional unit",
+                        l.unit, r.value
+                    ),
+                    span,
+                })?;
+                Some(result)
+            }
+            Op::Rem | Op::Mod | Op::FloorDiv => {
+                return Err(Error::UnsupportedOperator { op, span });
+            }
+        };
+        let result = result.ok_or_else(|| {
+            if l.dimension() == r.dimension() {
+                Error::MixedCurrencies {
+                    lhs: l.unit.to_string(),
+                    rhs: r.unit.to_string(),
+                    span,
+                }
+            } else {
+                Self::incompatible(&l.unit, &r.unit, span)
+            }
+        })?;
+        let value = self.check([&l.value, &r.value], result.value, span, || {
+            format!("'{op}' is undefined for {l} and {r}")
+        })?;
+        Self::value_from_quantity(Quantity::new(value, result.unit), span)
+    }
+
+    /// Date arithmetic: a date minus a date is a duration, and a date plus
+    /// or minus a duration is a date. A date is the start of its day in UTC
+    /// wherever it meets a datetime, and durations are rounded to the second
+    /// unless they are whole days added to a date.
+    fn eval_dates(&self, l: &Value<N>, op: Op, r: &Value<N>, span: Span) -> Result<Value<N>> {
+        let mismatch = || Self::mismatch(l, op, r, span);
+        let overflow = || Error::Overflow { span };
+        let to_datetime = |v: &Value<N>| match *v {
+            Value::Date(date) => Some(date.to_datetime()),
+            Value::DateTime(datetime) => Some(datetime),
+            _ => None,
+        };
+        // The duration in seconds, if `v` is a time.
+        let duration = |v: &Value<N>| match v {
+            Value::Quantity(q) if q.dimension() == Dimension::TIME && q.to_si().is_finite() => {
+                Some(q.to_si())
+            }
+            _ => None,
+        };
+
+        match (l, op, r) {
+            (Value::Date(a), Op::Sub, Value::Date(b)) => {
+                let days = (a.days() - b.days()) as f64;
+                let symbol = if days.abs() == 1.0 { "day" } else { "days" };
+                Self::value_from_quantity(Quantity::new(days, Self::unit(symbol, 86_400.0)), span)
+            }
+            (_, Op::Sub, _) if to_datetime(l).is_some() && to_datetime(r).is_some() => {
+                let (a, b) = to_datetime(l).zip(to_datetime(r)).expect("both are dates");
+                let seconds = (a.timestamp() - b.timestamp()) as f64;
+                Self::value_from_quantity(Quantity::new(seconds, Self::unit("s", 1.0)), span)
+            }
+            (_, Op::Add | Op::Sub, _) => {
+                let (date, shift) = match (to_datetime(l), op) {
+                    (Some(_), _) => (l, r),
+                    (None, Op::Add) => (r, l),
+                    (None, _) => return Err(mismatch()),
+                };
+                let seconds = match (duration(shift), op) {
+                    (Some(seconds), Op::Sub) => -seconds,
+                    (Some(seconds), _) => seconds,
+                    (None, _) => return Err(mismatch()),
+                };
+                if seconds.abs() > 1e16 {
+                    return Err(overflow());
+                }
+                match *date {
+                    Value::Date(date) if seconds % 86_400.0 == 0.0 => date
+                        .add_days((seconds / 86_400.0) as i64)
+                        .map(Value::Date)
+                        .ok_or_else(overflow),
+                    _ => to_datetime(date)
+                        .ok_or_else(mismatch)?
+                        .add_seconds(seconds.round() as i64)
+                        .map(Value::DateTime)
+                        .ok_or_else(overflow),
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// Evaluates a condition, which must be a boolean.
+    fn eval_condition(&mut self, ast: &Ast<N>) -> Result<bool> {
+        match self.eval(ast)? {
+            Value::Bool(b) => Ok(b),
+            value => Err(Error::TypeMismatch {
+                message: format!("expected a boolean, not {}", Self::describe(&value)),
+                span: ast.span(),
+            }),
+        }
+    }
+
+    /// Compares two values of the same kind. Numbers compare exactly in `N`
+    /// where they can, quantities in SI base units, and complex numbers,
+    /// booleans, lists and matrices can only be tested for equality.
+    fn compare(&self, l: &Value<N>, op: Comparison, r: &Value<N>, span: Span) -> Result<bool> {
+        let mismatch = || Self::mismatch(l, op, r, span);
+        let to_datetime = |v: &Value<N>| match *v {
+            Value::Date(date) => Some(date.to_datetime()),
+            Value::DateTime(datetime) => Some(datetime),
+            _ => None,
+        };
+
+        let ordering = match (l, r) {
+            (Value::Bool(a), Value::Bool(b)) if op.is_equality() => Some(a.cmp(b)),
+            (Value::Bool(_), _) | (_, Value::Bool(_)) => return Err(mismatch()),
+            (Value::List(a), Value::List(b)) if op.is_equality() => {
+                let equal = a.len() == b.len() && self.all_equal(a, b, span)?;
+                return Ok(equal == (op == Comparison::Eq));
+            }
+            (Value::Matrix(a), Value::Matrix(b)) if op.is_equality() => {
+                let equal = (a.rows(), a.cols()) == (b.rows(), b.cols())
+                    && self.all_equal(a.elements(), b.elements(), span)?;
+                return Ok(equal == (op == Comparison::Eq));
+            }
+            (Value::List(_) | Value::Matrix(_), _) | (_, Value::List(_) | Value::Matrix(_)) => {
+                return Err(mismatch())
+            }
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Date(_) | Value::DateTime(_), _) | (_, Value::Date(_) | Value::DateTime(_)) => {
+                let (a, b) = to_datetime(l).zip(to_datetime(r)).ok_or_else(mismatch)?;
+                Some(a.timestamp().cmp(&b.timestamp()))
+            }
+            (Value::Quantity(_), _) | (_, Value::Quantity(_)) => {
+                let (a, b) = l.to_quantity().zip(r.to_quantity()).ok_or_else(mismatch)?;
+                if a.dimension() != b.dimension() {
+                    return Err(Self::incompatible(&a.unit, &b.unit, span));
+                }
+                if a.unit.currencies() != b.unit.currencies() {
+                    return Err(Error::MixedCurrencies {
+                        lhs: a.unit.to_string(),
+                        rhs: b.unit.to_string(),
+                        span,
+                    });
+                }
+                a.to_si().partial_cmp(&b.to_si())
+            }
+            _ => match l.to_f64().zip(r.to_f64()) {
+                Some((a, b)) => a.partial_cmp(&b),
+                None if op.is_equality() => {
+                    let (a, b) = l.to_complex().zip(r.to_complex()).ok_or_else(mismatch)?;
+                    return Ok((a == b) == (op == Comparison::Eq));
+                }
+                None => return Err(mismatch()),
+            },
+        };
+        Ok(op.holds(ordering))
+    }
+
+    /// Whether the elements of `a` and `b`, which are as many, are equal in
+    /// pairs.
+    fn all_equal(&self, a: &[Value<N>], b: &[Value<N>], span: Span) -> Result<bool> {
+        for (a, b) in a.iter().zip(b) {
+            if !self.compare(a, Comparison::Eq, b, span)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// The error for an operator that is not defined for its operands.
+    fn mismatch(l: &Value<N>, op: impl fmt::Display, r: &Value<N>, span: Span) -> Error {
+        Error::TypeMismatch {
+            message: format!(
+                "'{}' is not defined for {} and {}",
+                op.to_string().trim(),
+                Self::describe(l),
+                Self::describe(r)
+            ),
+            span,
+        }
+    }
+
+    /// A description of the kind of `value`, for error messages.
+    fn describe(value: &Value<N>) -> String {
+        match value {
+            Value::Number(_) | Value::Float(_) => "a number".to_owned(),
+            Value::Complex(_) => "a complex number".to_owned(),
+            Value::Quantity(q) => format!("a quantity in {}", q.unit),
+            Value::Bool(_) => "a boolean".to_owned(),
+            Value::Date(_) => "a date".to_owned(),
+            Value::DateTime(_) => "a datetime".to_owned(),
+            Value::Function(_) => "a function".to_owned(),
+            Value::List(_) => "a list".to_owned(),
+            Value::Matrix(m) => format!("a {}×{} matrix", m.rows(), m.cols()),
+        }
+    }
+
+    /// The time unit `symbol` of `scale` seconds.
+    fn unit(symbol: &str, scale: f64) -> Unit {
+        Unit::from(NamedUnit {
+            symbol: symbol.to_owned(),
+            scale,
+            dimension: Dimension::TIME,
+        })
+    }
+
+    fn incompatible(lhs: &Unit, rhs: &Unit, span: Span) -> Error {
+        let describe = |unit: &Unit| {
+            if unit.is_one() {
+                "a plain number".to_owned()
+            } else {
+                unit.to_string()
+            }
+        };
+        Error::IncompatibleUnits {
+            lhs: describe(lhs),
+            rhs: describe(rhs),
+            span,
+        }
+    }
+
+    fn eval_complex(&self, l: Complex, op: Op, r: Complex, span: Span) -> Result<Value<N>> {
+        let result = match op {
+            Op::Add => l + r,
+            Op::Sub => l - r,
+            Op::Mul => l * r,
+            Op::Div => {
+                if self.options.strict && r.is_zero() {
+                    return Err(Error::DivisionByZero { span });
+                }
+                l / r
+            }
+            Op::Pow => l.pow(r),
+            Op::Rem | Op::Mod | Op::FloorDiv => {
+                return Err(Error::UnsupportedOperator { op, span });
+            }
+        };
+        let result = self.check_complex(&[l, r], result, span, || {
+            format!("'{op}' is undefined for {l} and {r}")
+        })?;
+        Self::value_from_complex(result, span)
+    }
+
+    fn apply<M: Numeric>(l: &M, op: Op, r: &M, ctx: &M::Context) -> number::Result<M> {
+        match op {
+            Op::Add => l.add(r, ctx),
+            Op::Sub => l.sub(r, ctx),
+            Op::Mul => l.mul(r, ctx),
+            Op::Div => l.div(r, ctx),
+            Op::Pow => l.pow(r, ctx),
+            Op::Rem => l.rem(r, ctx),
+            Op::Mod => l.modulo(r, ctx),
+            Op::FloorDiv => l.floor_div(r, ctx),
+        }
+    }
+
+    /// In strict mode, rejects a division by zero that produced a result,
+    /// as it does in float arithmetic.
+    fn check_division<M: Numeric>(&self, op: Op, divisor: &M, span: Span) -> Result<()> {
+        let divides = matches!(op, Op::Div | Op::Rem | Op::Mod | Op::FloorDiv);
+        if self.options.strict && divides && divisor.is_zero() {
+            return Err(Error::DivisionByZero { span });
+        }
+        Ok(())
+    }
+
+    /// In strict mode, rejects a NaN or infinite `result` computed from
+    /// operands that were all finite.
+    fn check<'a, M: Numeric + 'a>(
+        &self,
+        operands: impl IntoIterator<Item = &'a M>,
+        result: M,
+        span: Span,
+        domain_message: impl FnOnce() -> String,
+    ) -> Result<M> {
+        if self.options.strict && operands.into_iter().all(|n| n.is_finite()) {
+            if result.is_nan() {
+                return Err(Error::Domain {
+                    message: domain_message(),
+                    span,
+                });
+            }
+            if !result.is_finite() {
+                return Err(Error::Overflow { span });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Like [`Evaluator::check`], for complex numbers.
+    fn check_complex(
+        &self,
+        operands: &[Complex],
+        result: Complex,
+        span: Span,
+        domain_message: impl FnOnce() -> String,
+    ) -> Result<Complex> {
+        if self.options.strict && operands.iter().all(Complex::is_finite) {
+            if result.is_nan() {
+                return Err(Error::Domain {
+                    message: domain_message(),
+                    span,
+                });
+            }
+            if !result.is_finite() {
+                return Err(Error::Overflow { span });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Converts a complex result, which is a real number if its imaginary
+    /// part is zero. Complex numbers have `f64` parts, so they are results
+    /// that `N` cannot hold unless its results may be floats.
+    fn value_from_complex(z: Complex, span: Span) -> Result<Value<N>> {
+        if z.im == 0.0 {
+            Self::value_from_f64(z.re, span)
+        } else if N::FLOAT_FALLBACK {
+            Ok(Value::Complex(z))
+        } else {
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                span,
+            })
+        }
+    }
+
+    /// Converts a quantity, which is a plain number if its units cancel out.
+    /// Quantities have `f64` values, so like complex numbers they need `N`
+    /// to allow for floats.
+    fn value_from_quantity(q: Quantity, span: Span) -> Result<Value<N>> {
+        if q.dimension().is_none() {
+            Self::value_from_f64(q.to_si(), span)
+        } else if N::FLOAT_FALLBACK {
+            Ok(Value::Quantity(q))
+        } else {
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                span,
+            })
+        }
+    }
+
+    /// The value of a constant: a quantity if it has a unit, or a number if
+    /// it has none or its unit is not in the units table.
+    fn constant_value(&self, constant: &Constant, span: Span) -> Result<Value<N>> {
+        let unit = constant
+            .unit
+            .iter()
+            .try_fold(Unit::one(), |unit, &(symbol, power)| {
+                Some(unit.mul(&Unit::from(self.units.get(symbol)?).pow(power)))
+            });
+        match unit {
+            Some(unit) => Self::value_from_quantity(Quantity::new(constant.value, unit), span),
+            None => Self::value_from_f64(constant.value, span),
+        }
+    }
+
+    /// Converts a result computed in `f64` to `N`, keeping it as a float if
+    /// `N` cannot hold it but allows for that.
+    fn value_from_f64(x: f64, span: Span) -> Result<Value<N>> {
+        match N::from_f64(x) {
+            Some(n) => Ok(Value::Number(n)),
+            None if N::FLOAT_FALLBACK => Ok(Value::Float(x)),
+            None => Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                span,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        diagnostic::Severity,
+        lexer::{self, Lexer, TokenKind},
+        number::{
+            bigint::BigInt,
+            complex::Complex,
+            decimal::{Decimal, DecimalContext, Rounding},
+            integer::Division,
+            rational::Rational,
+        },
+        parser::{self, Parser},
+        span::Span,
+        units::currency::Rates,
+    };
+
+    fn exact() -> Evaluator<Rational> {
+        Evaluator::default()
+    }
+
+    fn decimal(precision: u32, rounding: Rounding) -> Evaluator<Decimal> {
+        let mut evaluator = Evaluator::default();
+        *evaluator.context_mut() = DecimalContext::default()
+            .with_precision(precision)
+            .unwrap()
+            .with_rounding(rounding);
+        evaluator
+    }
+
+    fn ratio(numer: i64, denom: i64) -> Value<Rational> {
+        Value::Number(Rational::new(BigInt::from(numer), BigInt::from(denom)).unwrap())
+    }
+
+    #[test]
+    fn test1() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("3"));
+        let expected = Value::Number(3.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test2() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-7"));
+        let expected = Value::Number(-7.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test3() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("+42"));
+        let expected = Value::Number(42.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test4() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 + 2"));
+        let expected = Value::Number(3.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test5() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("10 - 5"));
+        let expected = Value::Number(5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test6() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("5 + -3"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test7() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("5 - -3"));
+        let expected = Value::Number(8.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test8() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2 * 3"));
+        let expected = Value::Number(6.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test9() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("8 / 2"));
+        let expected = Value::Number(4.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test10() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-6 * 4"));
+        let expected = Value::Number(-24.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test11() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-8 / -4"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test12() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2 + 3 * 4"));
+        let expected = Value::Number(14.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test13() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("10 - 2 * 5"));
+        let expected = Value::Number(0.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test14() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("8 / 4 + 2"));
+        let expected = Value::Number(4.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test15() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("6 + 4 / 2"));
+        let expected = Value::Number(8.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test16() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("5 * 2 - 3"));
+        let expected = Value::Number(7.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test17() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(2 + 3) * 4 "));
+        let expected = Value::Number(20.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test18() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("10 / (5 - 3)"));
+        let expected = Value::Number(5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test19() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(8 + 2) * (3 - 1)"));
+        let expected = Value::Number(20.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test20() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(6 - 2) * (3 + 1)"));
+        let expected = Value::Number(16.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test21() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("((2 + 3) * (4 - 1)) + 5 "));
+        let expected = Value::Number(20.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test22() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("10 - (3 * (2 + 1)) "));
+        let expected = Value::Number(1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test23() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(4 / (1 + 1)) * (3 + 5)"));
+        let expected = Value::Number(16.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test24() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("3 * (2 + (4 - 1)) "));
+        let expected = Value::Number(15.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test25() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-(-3 + 5) "));
+        let expected = Value::Number(-2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test26() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-(3 + -2) "));
+        let expected = Value::Number(-1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test27() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("+(3 + -4)"));
+        let expected = Value::Number(-1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test28() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-((2 + 3) * 4)"));
+        let expected = Value::Number(-20.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test29() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("+5"));
+        let expected = Value::Number(5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test30() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-5"));
+        let expected = Value::Number(-5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+    #[test]
+    fn test31() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-(-5)"));
+        let expected = Value::Number(5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test32() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("+(-5)"));
+        let expected = Value::Number(-5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test33() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("3 + (4 * 2) / (1 - 5)"));
+        let expected = Value::Number(1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test34() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(8 - 3) * (-2) "));
+        let expected = Value::Number(-10.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test35() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(10 - (3 * 2 + 1))"));
+        let expected = Value::Number(3.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test36() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("6 + (8 - (2 * 3)) * 4 "));
+        let expected = Value::Number(14.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test37() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("1 +\n (2 * 3)"));
+        let ast = parser.parse()?;
+
+        assert_eq!(Span::new(0, 12, 1, 1), ast.span());
+        match ast {
+            Ast::BinaryExpr { rhs, .. } => assert_eq!(Span::new(6, 11, 2, 3), rhs.span()),
+            _ => panic!("expected a binary expression, got {ast:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test38() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("1 2"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::UnexpectedToken { .. }) => {
+                assert_eq!(Span::new(2, 3, 1, 3), err.span())
+            }
+            res => panic!("expected an unexpected token error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test39() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("3 )"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::UnexpectedToken { .. }) => {
+                assert_eq!(Span::new(2, 3, 1, 3), err.span())
+            }
+            res => panic!("expected an unexpected token error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test40() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("4 5 + 6"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::UnexpectedToken { .. }) => {
+                assert_eq!(Span::new(2, 3, 1, 3), err.span())
+            }
+            res => panic!("expected an unexpected token error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test41() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("(1 + 2) (3)"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::UnexpectedToken { .. }) => {
+                assert_eq!(Span::new(8, 9, 1, 9), err.span())
+            }
+            res => panic!("expected an unexpected token error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test42() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("2 * 3 4 * 5"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::UnexpectedToken { .. }) => {
+                assert_eq!(Span::new(6, 7, 1, 7), err.span())
+            }
+            res => panic!("expected an unexpected token error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test43() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("2 $ 3"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(err @ lexer::Error::InvalidCharacter('$', span))) => {
+                assert_eq!(Span::new(2, 3, 1, 3), span);
+                assert_eq!(
+                    vec![
+                        "expressions are made of numbers, @dates, names of variables, \
+                         functions and units, operators such as + and ->, `? :`, \
+                         parentheses, brackets and commas"
+                    ],
+                    err.diagnostic().help
+                );
+            }
+            res => panic!("expected an invalid character error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test44() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("(1 + 2) * 3 #"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::InvalidCharacter('#', span))) => {
+                assert_eq!(Span::new(12, 13, 1, 13), span)
+            }
+            res => panic!("expected an invalid character error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test45() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1e-9"));
+        let expected = Value::Number(1e-9);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test46() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("6.02E23 / 2"));
+        let expected = Value::Number(3.01e23);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test47() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("0xff + 0o755"));
+        let expected = Value::Number(748.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test48() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("0b1010 * 1_000_000"));
+        let expected = Value::Number(10_000_000.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test49() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2.5e+2 - 1_0.5"));
+        let expected = Value::Number(239.5);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test50() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("0XFF - 0B1"));
+        let expected = Value::Number(254.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test51() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("1.2.3"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("a number can only have one decimal point", reason);
+                assert_eq!(Span::new(0, 4, 1, 1), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test52() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("0b102"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("'2' is not a valid digit in base 2", reason);
+                assert_eq!(Span::new(0, 5, 1, 1), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test53() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("1__000"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("'_' must separate two digits", reason);
+                assert_eq!(Span::new(0, 2, 1, 1), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test54() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("2 * 0x"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("expected digits after the radix prefix", reason);
+                assert_eq!(Span::new(4, 6, 1, 5), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test55() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("1e5.5"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("the exponent must be an integer", reason);
+                assert_eq!(Span::new(0, 4, 1, 1), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test56() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("100_"));
+
+        match parser.parse() {
+            Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                assert_eq!("'_' must separate two digits", reason);
+                assert_eq!(Span::new(0, 4, 1, 1), span);
+            }
+            res => panic!("expected a malformed number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test57() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2^3^2"));
+        let expected = Value::Number(512.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test58() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-2^2"));
+        let expected = Value::Number(-4.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test59() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2 ** 3 ** 2"));
+        let expected = Value::Number(512.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test60() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2^-1"));
+        let expected = Value::Number(0.5);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test61() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(-2)^2"));
+        let expected = Value::Number(4.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test62() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("3 * 2^2 + 1"));
+        let expected = Value::Number(13.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test63() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2^3 * 2"));
+        let expected = Value::Number(16.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test64() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-2**-2"));
+        let expected = Value::Number(-0.25);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test65() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("7 % 3"));
+        let expected = Value::Number(1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test66() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-7 % 3"));
+        let expected = Value::Number(-1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test67() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("7 % -3"));
+        let expected = Value::Number(1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test68() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-7 mod 3"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test69() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("7 mod -3"));
+        let expected = Value::Number(-2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test70() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-7 mod -3"));
+        let expected = Value::Number(-1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test71() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("7 // 2"));
+        let expected = Value::Number(3.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test72() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("-7 // 2"));
+        let expected = Value::Number(-4.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test73() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 + 10 // 3 * 2"));
+        let expected = Value::Number(7.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test74() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2 * 9 mod 4"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test75() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("5.5 % 2"));
+        let expected = Value::Number(1.5);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test76() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let mut parser = Parser::new(Lexer::new("x = 3"));
+        assert_eq!(Value::Number(3.0), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("x * 2"));
+        assert_eq!(Value::Number(6.0), evaluator.eval(&parser.parse()?)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test77() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        for (input, expected) in [
+            ("x = 1", 1.0),
+            ("x = x + 1", 2.0),
+            ("y = x = 5", 5.0),
+            ("x * y", 25.0),
+        ] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(Value::Number(expected), evaluator.eval(&parser.parse()?)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test78() -> eyre::Result<()> {
+        let env = [("rate", 0.25), ("hours", 8.0)].into_iter().collect();
+        let mut evaluator = Evaluator::with_environment(env);
+        let mut parser = Parser::new(Lexer::new("rate * hours"));
+        let expected = Value::Number(2.0);
+        let actual = evaluator.eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test79() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 + foo"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(Error::UndefinedVariable { name, span }) => {
+                assert_eq!("foo", name);
+                assert_eq!(Span::new(4, 7, 1, 5), span);
+            }
+            res => panic!("expected an undefined variable error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test80() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("2 * x = 3"));
+
+        match parser.parse() {
+            Err(err @ parser::Error::InvalidAssignmentTarget { .. }) => {
+                assert_eq!(Span::new(0, 5, 1, 1), err.span())
+            }
+            res => panic!("expected an invalid assignment target error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test81() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("sqrt(16) + cbrt(27)"));
+        let expected = Value::Number(7.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test82() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("max(1, 5, 3) - min(4, 2)"));
+        let expected = Value::Number(3.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test83() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("log(8, 2) + log10(1000) + log2(4)"));
+        let expected = Value::Number(8.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test84() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("hypot(3, 4)"));
+        let expected = Value::Number(5.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test85() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new(
+            "abs(-2.5) + floor(2.7) + ceil(2.1) + round(2.5)",
+        ));
+        let expected = Value::Number(10.5);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test86() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("atan2(1, 1) * 4"));
+        let expected = Value::Number(std::f64::consts::PI);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test87() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("ln(exp(2))"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test88() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new(
+            "sin(0) + cos(0) + tan(0) + asin(0) + acos(1) + atan(0)",
+        ));
+        let expected = Value::Number(1.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test89() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("2 * sqrt(max(2, 8) * 2)"));
+        let expected = Value::Number(8.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test90() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("sqrt(4, 9)"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(err @ Error::WrongArgumentCount { .. }) => {
+                assert_eq!("sqrt expects 1 argument, got 2", err.to_string());
+                assert_eq!(Span::new(0, 10, 1, 1), err.span());
+            }
+            res => panic!("expected a wrong argument count error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test91() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("atan2(1) + frobnicate()"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(err @ Error::WrongArgumentCount { .. }) => {
+                assert_eq!("atan2 expects 2 arguments, got 1", err.to_string())
+            }
+            res => panic!("expected a wrong argument count error, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("frobnicate()"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::UnknownFunction { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test92() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+        evaluator
+            .functions_mut()
+            .register("double", Arity::Exact(1), |args| args[0] * 2.0);
+        let mut parser = Parser::new(Lexer::new("double(21)"));
+        let expected = Value::Number(42.0);
+        let actual = evaluator.eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test93() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("tau / 2 - pi + ln(e) + phi^2 - phi"));
+        let expected = Value::Number(2.0);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test94() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let mut parser = Parser::new(Lexer::new("-inf"));
+        assert_eq!(
+            Value::Number(f64::NEG_INFINITY),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        let mut parser = Parser::new(Lexer::new("nan + 1"));
+        assert!(matches!(evaluator.eval(&parser.parse()?)?, Value::Number(n) if n.is_nan()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test95() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("pi = 3"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(Error::AssignToConstant { name, span }) => {
+                assert_eq!("pi", name);
+                assert_eq!(Span::new(0, 6, 1, 1), span);
+            }
+            res => panic!("expected an assignment to constant error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test96() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let mut parser = Parser::new(Lexer::new("k_B * N_A"));
+        let ast = parser.parse()?;
+        assert!(matches!(
+            evaluator.eval(&ast),
+            Err(Error::UndefinedVariable { .. })
+        ));
+
+        evaluator.constants_mut().add_physical();
+        let expected = 8.314_462_618_153_24;
+        let actual = evaluator.eval(&ast)?.to_quantity().unwrap();
+        assert!((expected - actual.value).abs() < 1e-12);
+        assert_eq!("J/(K*mol)", actual.unit.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test97() -> eyre::Result<()> {
+        for (input, start, end) in [
+            ("1 / 0", 0, 5),
+            ("2 * (3 // (1 - 1))", 5, 17),
+            ("4 mod 0", 0, 7),
+            ("5 % 0", 0, 5),
+        ] {
+            let mut parser = Parser::new(Lexer::new(input));
+
+            match Evaluator::new().eval(&parser.parse()?) {
+                Err(Error::DivisionByZero { span }) => {
+                    assert_eq!(Span::new(start, end, 1, start + 1), span)
+                }
+                res => panic!("expected a division by zero error for {input}, got {res:?}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test98() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 + asin(2)"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(err @ Error::Domain { .. }) => {
+                assert_eq!("asin is undefined for these arguments", err.to_string());
+                assert_eq!(Span::new(4, 11, 1, 5), err.span());
+            }
+            res => panic!("expected a domain error, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("log(-8, 2)"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::Domain { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test99() -> eyre::Result<()> {
+        for input in ["10 ^ 400", "1e308 * 10", "exp(1000)"] {
+            let mut parser = Parser::new(Lexer::new(input));
+
+            assert!(
+                matches!(
+                    Evaluator::new().eval(&parser.parse()?),
+                    Err(Error::Overflow { .. })
+                ),
+                "expected an overflow error for {input}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test100() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::with_options(EvalOptions {
+            strict: false,
+            ..EvalOptions::default()
+        });
+
+        let mut parser = Parser::new(Lexer::new("1 / 0"));
+        assert_eq!(
+            Value::Number(f64::INFINITY),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        let mut parser = Parser::new(Lexer::new("asin(2)"));
+        assert!(matches!(evaluator.eval(&parser.parse()?)?, Value::Number(n) if n.is_nan()));
+
+        let mut parser = Parser::new(Lexer::new("10 ^ 400"));
+        assert_eq!(
+            Value::Number(f64::INFINITY),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test101() -> eyre::Result<()> {
+        let ast = Ast::UnaryExpr {
+            op: Op::Mul,
+            rhs: Box::new(Ast::Number {
+                value: 2.0,
+                span: Span::new(1, 2, 1, 2),
+            }),
+            span: Span::new(0, 2, 1, 1),
+        };
+
+        match Evaluator::new().eval(&ast) {
+            Err(Error::UnsupportedOperator { op, span }) => {
+                assert_eq!(Op::Mul, op);
+                assert_eq!(Span::new(0, 2, 1, 1), span);
+            }
+            res => panic!("expected an unsupported operator error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test102() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("inf - 1 + 2 * inf"));
+        let expected = Value::Number(f64::INFINITY);
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test103() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1/3 + 1/6"));
+        let expected = ratio(1, 2);
+        let actual = exact().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+        assert_eq!("1/2", actual.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test104() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("0.1 + 0.2 - 1.5e-1"));
+        let expected = ratio(3, 20);
+        let actual = exact().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test105() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("float(1/3)"));
+        let expected = Value::Float(1.0 / 3.0);
+        let actual = exact().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test106() -> eyre::Result<()> {
+        let mut evaluator = exact();
+
+        let mut parser = Parser::new(Lexer::new("-(2/3)^-3 + -7 mod 3 - 7 // 2"));
+        assert_eq!(ratio(-35, 8), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("4^0.5"));
+        assert_eq!(Value::Float(2.0), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("1/2 + pi"));
+        assert_eq!(
+            Value::Float(0.5 + std::f64::consts::PI),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test107() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1/3 / (1/2 - 2/4)"));
+
+        match exact().eval(&parser.parse()?) {
+            Err(Error::Arithmetic {
+                source: number::Error::DivisionByZero,
+                span,
+            }) => {
+                assert_eq!(Span::new(0, 17, 1, 1), span);
+            }
+            res => panic!("expected a division by zero error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test108() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1/3"));
+        let expected = "0.33333333333333333333333333333333333333333333333333";
+        let actual = decimal(50, Rounding::HalfEven).eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test109() -> eyre::Result<()> {
+        let ast = Parser::new(Lexer::new("2/3 + 0.125")).parse()?;
+
+        let results = [
+            (Rounding::HalfEven, "0.792"),
+            (Rounding::HalfUp, "0.792"),
+            (Rounding::TowardZero, "0.791"),
+        ];
+        for (rounding, expected) in results {
+            let actual = decimal(3, rounding).eval(&ast)?;
+            assert_eq!(expected, actual.to_string());
+        }
+
+        let ast = Parser::new(Lexer::new("0.125 * 1")).parse()?;
+        assert_eq!(
+            "0.12",
+            decimal(2, Rounding::HalfEven).eval(&ast)?.to_string()
+        );
+        assert_eq!("0.13", decimal(2, Rounding::HalfUp).eval(&ast)?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test110() -> eyre::Result<()> {
+        let mut evaluator = decimal(50, Rounding::HalfEven);
+
+        let mut parser = Parser::new(Lexer::new("-(0.1 + 0.2) * 10 // 1 + 2^-2"));
+        assert_eq!("-2.75", evaluator.eval(&parser.parse()?)?.to_string());
+
+        let mut parser = Parser::new(Lexer::new("float(1/3)"));
+        assert_eq!(Value::Float(1.0 / 3.0), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("1 / (0.5 - 0.5)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::DivisionByZero,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test111() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("0.1 + 0.2"));
+        let expected = Value::Number(0.3f32);
+        let actual = Evaluator::<f32>::default().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test112() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::<i64>::default();
+
+        let mut parser = Parser::new(Lexer::new("-7 mod 3 + 2^10 + 0x10 // 3 - 1e3"));
+        assert_eq!(Value::Number(31), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("9223372036854775807 + 1"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Overflow,
+                ..
+            })
+        ));
+
+        let mut parser = Parser::new(Lexer::new("7 / 2"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
+
+        let mut parser = Parser::new(Lexer::new("sqrt(16) + sqrt(2)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test113() -> eyre::Result<()> {
+        let mut parser = Parser::<i64>::new(Lexer::new("1 + 1.5"));
+
+        match parser.parse() {
+            Err(parser::Error::Number {
+                source: number::Error::Inexact,
+                span,
+            }) => {
+                assert_eq!(Span::new(4, 7, 1, 5), span);
+            }
+            res => panic!("expected an invalid number error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test114() -> eyre::Result<()> {
+        fn eval<N: Numeric>(input: &str) -> eyre::Result<String> {
+            let ast = Parser::<N>::new(Lexer::new(input)).parse()?;
+            Ok(Evaluator::<N>::default().eval(&ast)?.to_string())
+        }
+
+        let input = "(2 + 3) * 4 - 10 // 4";
+        assert_eq!("18", eval::<f64>(input)?);
+        assert_eq!("18", eval::<f32>(input)?);
+        assert_eq!("18", eval::<i64>(input)?);
+        assert_eq!("18", eval::<Rational>(input)?);
+        assert_eq!("18", eval::<Decimal>(input)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test115() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::<i128>::default();
+
+        let mut parser = Parser::new(Lexer::new("2^100 + 0xffff_ffff_ffff_ffff * 3"));
+        let expected = Value::Number((1i128 << 100) + 0xffff_ffff_ffff_ffff * 3);
+        assert_eq!(expected, evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("2 * 2^126"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(Error::Arithmetic {
+                source: number::Error::Overflow,
+                span,
+            }) => {
+                assert_eq!(Span::new(0, 9, 1, 1), span);
+            }
+            res => panic!("expected an overflow error, got {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test116() -> eyre::Result<()> {
+        let ast = Parser::new(Lexer::new("-7 / 2")).parse()?;
+
+        let results = [
+            (Division::Truncate, Value::Number(-3)),
+            (Division::Floor, Value::Number(-4)),
+        ];
+        for (division, expected) in results {
+            let mut evaluator = Evaluator::<i128>::default();
+            *evaluator.context_mut() = division;
+            assert_eq!(expected, evaluator.eval(&ast)?);
+        }
+
+        assert!(matches!(
+            Evaluator::<i128>::default().eval(&ast),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test117() -> eyre::Result<()> {
+        let kinds = Lexer::new("3+4i - 2.5j * 1e3i + 3in")
+            .map(|tok| tok.map(|tok| tok.kind))
+            .collect::<lexer::Result<Vec<_>>>()?;
+        let expected = [
+            TokenKind::Number,
+            TokenKind::Plus,
+            TokenKind::Imaginary,
+            TokenKind::Minus,
+            TokenKind::Imaginary,
+            TokenKind::Asterisk,
+            TokenKind::Imaginary,
+            TokenKind::Plus,
+            TokenKind::Number,
+            TokenKind::In,
+        ];
+
+        assert_eq!(expected.as_slice(), kinds);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test118() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("(3+4i) * (1-2i) / i"));
+        let expected = Value::Complex(Complex::new(-2.0, -11.0));
+        let actual = Evaluator::new().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+        assert_eq!("-2-11i", actual.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test119() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let results = [
+            ("sqrt(-1)", Value::Complex(Complex::I)),
+            ("i * j", Value::Number(-1.0)),
+            ("abs(3+4i)", Value::Number(5.0)),
+            ("arg(-2)", Value::Number(std::f64::consts::PI)),
+            ("conj(3+4i)", Value::Complex(Complex::new(3.0, -4.0))),
+            ("re(3+4i) + im(3+4i)", Value::Number(7.0)),
+            ("sqrt(-4) ^ 2", Value::Number(-4.0)),
+            ("-(1-i)", Value::Complex(Complex::new(-1.0, 1.0))),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, evaluator.eval(&parser.parse()?)?, "{input}");
+        }
+
+        let mut parser = Parser::new(Lexer::new("exp(i*pi)"));
+        let Value::Complex(z) = evaluator.eval(&parser.parse()?)? else {
+            panic!("expected a complex result");
+        };
+        assert!((z - Complex::from(-1.0)).abs() < 1e-15);
+
+        let mut parser = Parser::new(Lexer::new("(-8) ^ (1/3)"));
+        let Value::Complex(z) = evaluator.eval(&parser.parse()?)? else {
+            panic!("expected a complex result");
+        };
+        assert!((z - Complex::new(1.0, 3f64.sqrt())).abs() < 1e-15);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test120() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let mut parser = Parser::new(Lexer::new("i = 2"));
+        evaluator.eval(&parser.parse()?)?;
+        let mut parser = Parser::new(Lexer::new("i + 1j"));
+        assert_eq!(
+            Value::Complex(Complex::new(2.0, 1.0)),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        let mut parser = Parser::new(Lexer::new("floor(2 + 1j)"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(err @ Error::ComplexArgument { .. }) => {
+                assert_eq!("floor is not defined for complex numbers", err.to_string());
+                assert_eq!(Span::new(0, 13, 1, 1), err.span());
+            }
+            res => panic!("expected a complex argument error, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("(1 + 2j) mod 2"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::UnsupportedOperator { op: Op::Mod, .. })
+        ));
+
+        let mut parser = Parser::new(Lexer::new("(1 + 2j) / 0"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::DivisionByZero { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test121() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1/2 + 3/4i"));
+        let expected = Value::Complex(Complex::new(0.5, -0.75));
+        let actual = exact().eval(&parser.parse()?)?;
+
+        assert_eq!(expected, actual);
+
+        let mut parser = Parser::new(Lexer::new("2 + 3i"));
+        assert!(matches!(
+            Evaluator::<i64>::default().eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test122() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let results = [
+            ("3 m / 2 s", "1.5 m/s"),
+            ("5 km + 300 m", "5.3 km"),
+            ("72 km/h to m/s", "20 m/s"),
+            ("60 mph in km/h", "96.56064 km/h"),
+            ("-(4 m^2)^0.5 * 3", "-6 m"),
+            ("1 kg * 9.81 m/s^2 to N", "9.81 N"),
+            ("2 ft * 3 ft", "6 ft^2"),
+            ("5 km / 500 m", "10"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, evaluator.eval(&parser.parse()?)?.to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test123() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 m + 1 s"));
+
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(err @ Error::IncompatibleUnits { .. }) => {
+                assert_eq!("incompatible units: m and s", err.to_string());
+                assert_eq!(Span::new(0, 9, 1, 1), err.span());
+            }
+            res => panic!("expected an incompatible units error, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("3 kg to m"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::IncompatibleUnits { .. })
+        ));
+
+        for input in ["2 ^ (1 s)", "(2 m)^0.5", "sqrt(2 m)", "1 m to 2 m"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(
+                matches!(
+                    Evaluator::new().eval(&parser.parse()?),
+                    Err(Error::InvalidUnits { .. })
+                ),
+                "expected an invalid units error for {input}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test124() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+
+        let mut parser = Parser::new(Lexer::new("m = 3"));
+        evaluator.eval(&parser.parse()?)?;
+        let mut parser = Parser::new(Lexer::new("2 m + 2 pi"));
+        assert_eq!(
+            Value::Number(6.0 + 2.0 * std::f64::consts::PI),
+            evaluator.eval(&parser.parse()?)?
+        );
+
+        let mut parser = Parser::new(Lexer::new("2 km"));
+        assert!(matches!(
+            Evaluator::<i64>::default().eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    fn with_rates() -> Evaluator {
+        let mut evaluator = Evaluator::new();
+        let rates = Rates::from_json(
+            r#"{"base": "EUR", "date": "2026-10-16", "rates": {"USD": 1.25, "GBP": 0.8}}"#,
+        )
+        .unwrap();
+        evaluator.units_mut().set_rates(&rates);
+        evaluator
+    }
+
+    #[test]
+    fn test125() -> eyre::Result<()> {
+        let mut evaluator = with_rates();
+
+        let results = [
+            ("100 USD to EUR", "80 EUR", 1),
+            ("10 GBP + 5 GBP in USD", "23.4375 USD", 1),
+            ("2 EUR/kg * 3 kg", "6 EUR", 0),
+        ];
+        for (input, expected, notes) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, evaluator.eval(&parser.parse()?)?.to_string());
+
+            let diagnostics = evaluator.take_diagnostics();
+            assert_eq!(notes, diagnostics.len());
+            for diagnostic in diagnostics {
+                assert_eq!(Severity::Note, diagnostic.severity);
+                assert_eq!("exchange rates as of 2026-10-16", diagnostic.message);
+            }
+        }
 
-    #[test]
-    fn test1() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("3"));
-        let expected = 3.0;
-        let actual = Evaluator::eval(parser.parse()?);
+        let mut parser = Parser::new(Lexer::new("5 EUR to EUR"));
+        evaluator.eval(&parser.parse()?)?;
+        assert!(evaluator.take_diagnostics().is_empty());
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("100 USD / 50 EUR", "1.6", 1),
+            ("[100 USD, 50 USD] / 50 EUR", "[1.6, 0.8]", 1),
+            ("(10 USD / 1 EUR) * (2 EUR / 1 GBP)", "12.8", 1),
+            ("100 USD / 50 USD", "2", 0),
+            ("100 USD / 2 kg", "50 USD/kg", 0),
+        ];
+        for (input, expected, notes) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+            let diagnostics = evaluator.take_diagnostics();
+            assert_eq!(notes, diagnostics.len(), "for {input}");
+            for diagnostic in diagnostics {
+                assert_eq!("exchange rates as of 2026-10-16", diagnostic.message);
+            }
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test2() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-7"));
-        let expected = -7.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test126() -> eyre::Result<()> {
+        let mut parser = Parser::new(Lexer::new("1 USD + 1 EUR"));
 
-        assert_eq!(expected, actual);
+        match with_rates().eval(&parser.parse()?) {
+            Err(err @ Error::MixedCurrencies { .. }) => {
+                assert_eq!("cannot mix currencies: USD and EUR", err.to_string());
+            }
+            res => panic!("expected a mixed currencies error, got {res:?}"),
+        }
+
+        for input in ["1 USD + 1 kg", "1 USD to m"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(matches!(
+                with_rates().eval(&parser.parse()?),
+                Err(Error::IncompatibleUnits { .. })
+            ));
+        }
+
+        let mut parser = Parser::new(Lexer::new("1 USD"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::UndefinedVariable { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test3() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("+42"));
-        let expected = 42.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test127() -> eyre::Result<()> {
+        let tokens = Lexer::new("@2026-10-18T14:30+02:00 - @2026-10-18 + @2026-10-18-1")
+            .map(|tok| tok.map(|tok| (tok.kind, tok.spelling)))
+            .collect::<lexer::Result<Vec<_>>>()?;
+        let expected = [
+            (TokenKind::Date, "@2026-10-18T14:30+02:00"),
+            (TokenKind::Minus, "-"),
+            (TokenKind::Date, "@2026-10-18"),
+            (TokenKind::Plus, "+"),
+            (TokenKind::Date, "@2026-10-18"),
+            (TokenKind::Minus, "-"),
+            (TokenKind::Number, "1"),
+        ];
+        assert_eq!(expected.as_slice(), tokens);
 
-        assert_eq!(expected, actual);
+        for input in [
+            "@2026-10-18T14",
+            "@2026-10-188",
+            "@2026-10-18T14:30:00:00",
+            "@26-10-18",
+            "@",
+        ] {
+            assert!(
+                matches!(
+                    Lexer::new(input).lex(),
+                    Err(lexer::Error::MalformedDate(..))
+                ),
+                "expected a malformed date error for {input}"
+            );
+        }
 
-        Ok(())
-    }
+        let mut parser: Parser = Parser::new(Lexer::new("@2026-13-01"));
+        match parser.parse() {
+            Err(err @ parser::Error::Date { .. }) => {
+                assert_eq!("invalid date: there is no month 13", err.to_string());
+                assert_eq!(Span::new(0, 11, 1, 1), err.span());
+            }
+            res => panic!("expected an invalid date error, got {res:?}"),
+        }
 
-    #[test]
-    fn test4() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("1 + 2"));
-        let expected = 3.0;
-        let actual = Evaluator::eval(parser.parse()?);
+        // Without the '@' this is a subtraction, which the lexer warns about.
+        let mut parser = Parser::new(Lexer::new("1000-10-10"));
+        assert_eq!(
+            Value::Number(980.0),
+            Evaluator::new().eval(&parser.parse()?)?
+        );
+        let warnings = parser.take_diagnostics();
+        assert_eq!(1, warnings.len());
+        assert_eq!(
+            Diagnostic::warning("'1000-10-10' is a subtraction, not a date")
+                .with_label(Span::new(0, 10, 1, 1), "read as 1000 - 10 - 10")
+                .with_help("write @1000-10-10 for the date"),
+            warnings[0]
+        );
 
-        assert_eq!(expected, actual);
+        let mut parser: Parser = Parser::new(Lexer::new("2026-10-18 + 90 days"));
+        parser.parse()?;
+        assert_eq!(1, parser.take_diagnostics().len());
+
+        for input in ["1000 - 10 - 10", "@2026-10-18", "2026-10-188", "12-10-18"] {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            parser.parse()?;
+            assert!(
+                parser.take_diagnostics().is_empty(),
+                "unexpected warning for {input}"
+            );
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test5() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("10 - 5"));
-        let expected = 5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test128() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("@2026-10-18 + 90 days", "2027-01-16"),
+            ("90 days + @2026-10-18", "2027-01-16"),
+            ("@2026-03-01 - 1 day", "2026-02-28"),
+            ("(@2026-12-25 - @2026-10-18) in days", "68 days"),
+            ("@2026-12-25 - @2026-10-18", "68 days"),
+            ("@2026-10-19 - @2026-10-18", "1 day"),
+            (
+                "@2026-12-25 - @2026-10-18 to weeks",
+                "9.714285714285714 weeks",
+            ),
+            ("@2026-10-18 + 36 h", "2026-10-19T12:00:00Z"),
+            ("@2026-10-18T23:30+02:00 + 1 h", "2026-10-19T00:30:00+02:00"),
+            ("@2026-10-18T14:30+02:00 - @2026-10-18T09:00Z", "12600 s"),
+            ("@2026-10-18T12:00Z - @2026-10-18", "43200 s"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("today - today"));
+        assert_eq!("0 days", evaluator.eval(&parser.parse()?)?.to_string());
 
         Ok(())
     }
 
     #[test]
-    fn test6() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("5 + -3"));
-        let expected = 2.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test129() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("3h 20min", "200 min"),
+            ("3h 20min * 4", "800 min"),
+            ("2 h 29 min 60 s to h", "2.5 h"),
+            ("1 km 500 m", "1500 m"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, evaluator.eval(&parser.parse()?)?.to_string());
+        }
 
-        Ok(())
-    }
+        let mut parser: Parser = Parser::new(Lexer::new("1 h 2 min 3 s / 2"));
+        assert_eq!("1*h 2*min 3*s/2", parser.parse()?.to_string());
 
-    #[test]
-    fn test7() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("5 - -3"));
-        let expected = 8.0;
-        let actual = Evaluator::eval(parser.parse()?);
+        for input in ["2 3", "(2 m) 3 m"] {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert!(parser.parse().is_err(), "expected {input} not to parse");
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("1 h 20 m"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::IncompatibleUnits { .. })
+        ));
+        let mut parser = Parser::new(Lexer::new("2 m 3"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(err @ Error::InvalidUnits { .. }) => assert_eq!(Span::new(4, 5, 1, 5), err.span()),
+            res => panic!("expected an invalid units error, got {res:?}"),
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test8() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("2 * 3"));
-        let expected = 6.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test130() -> eyre::Result<()> {
+        let inputs = [
+            (
+                "@2026-10-18 + 3",
+                "'+' is not defined for a date and a number",
+            ),
+            (
+                "@2026-10-18 + @2026-10-18",
+                "'+' is not defined for a date and a date",
+            ),
+            (
+                "3 days - @2026-10-18",
+                "'-' is not defined for a quantity in days and a date",
+            ),
+            (
+                "@2026-10-18 * 2",
+                "'*' is not defined for a date and a number",
+            ),
+            (
+                "@2026-10-18 + 3 m",
+                "'+' is not defined for a date and a quantity in m",
+            ),
+            ("sqrt(@2026-10-18)", "sqrt is not defined for dates"),
+            ("today to days", "Dates cannot be converted to units"),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match Evaluator::new().eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("@2026-10-18 + 1e20 s"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::Overflow { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test9() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("8 / 2"));
-        let expected = 4.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test131() -> eyre::Result<()> {
+        let kinds = Lexer::new("== != <= < >= > = ? : if then else and or not true false")
+            .map(|tok| tok.map(|tok| tok.kind))
+            .collect::<lexer::Result<Vec<_>>>()?;
+        let expected = [
+            TokenKind::DoubleEquals,
+            TokenKind::BangEquals,
+            TokenKind::LessEquals,
+            TokenKind::Less,
+            TokenKind::GreaterEquals,
+            TokenKind::Greater,
+            TokenKind::Equals,
+            TokenKind::Question,
+            TokenKind::Colon,
+            TokenKind::If,
+            TokenKind::Then,
+            TokenKind::Else,
+            TokenKind::And,
+            TokenKind::Or,
+            TokenKind::Not,
+            TokenKind::True,
+            TokenKind::False,
+        ];
+        assert_eq!(expected.as_slice(), kinds);
 
-        assert_eq!(expected, actual);
+        assert!(matches!(
+            Lexer::new("!x").lex(),
+            Err(lexer::Error::InvalidCharacter('!', _))
+        ));
+
+        for input in ["1 < 2 < 3", "if true then 1", "true ? 1", "1 == == 2"] {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert!(parser.parse().is_err(), "expected {input} not to parse");
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test10() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-6 * 4"));
-        let expected = -24.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test132() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("x = -3", "-3"),
+            ("if x > 0 then x else -x", "3"),
+            ("x > 0 ? x : -x", "3"),
+            ("x < 0 ? -1 : x == 0 ? 0 : 1", "-1"),
+            ("1 + 1 == 2 and 2 * 3 > 5 or false", "true"),
+            ("not 1 > 2 and 2 != 2", "false"),
+            ("not not true", "true"),
+            ("(1 < 2) == (3 >= 3)", "true"),
+            ("max(if true then 1 else 2, 0)", "1"),
+            ("0.1 + 0.2 == 0.3", "false"),
+            ("1 km >= 1000 m", "true"),
+            ("@2026-10-18 < @2026-10-18T00:00:01Z", "true"),
+            ("1 + 2i == 1 + 2i", "true"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("1/10 + 2/10 == 3/10"));
+        assert_eq!(Value::Bool(true), exact().eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("0/0 != 0/0"));
+        let mut evaluator = Evaluator::with_options(EvalOptions {
+            strict: false,
+            ..EvalOptions::default()
+        });
+        assert_eq!(Value::Bool(true), evaluator.eval(&parser.parse()?)?);
 
         Ok(())
     }
 
     #[test]
-    fn test11() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-8 / -4"));
-        let expected = 2.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test133() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        for input in [
+            "false and nothing > 0",
+            "true or nothing > 0",
+            "if true then 1 else 1/0",
+            "false ? 1/0 : 2",
+        ] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(
+                evaluator.eval(&parser.parse()?).is_ok(),
+                "expected {input} to skip its unevaluated side"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("true and nothing > 0"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::UndefinedVariable { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test12() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("2 + 3 * 4"));
-        let expected = 14.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test134() -> eyre::Result<()> {
+        let inputs = [
+            ("true + 1", "'+' is not defined for a boolean and a number"),
+            (
+                "1 == true",
+                "'==' is not defined for a number and a boolean",
+            ),
+            (
+                "true < false",
+                "'<' is not defined for a boolean and a boolean",
+            ),
+            (
+                "1i < 2i",
+                "'<' is not defined for a complex number and a complex number",
+            ),
+            ("-true", "'-' is not defined for a boolean"),
+            ("sqrt(true)", "sqrt is not defined for booleans"),
+            ("not 3", "expected a boolean, not a number"),
+            ("true and 1 m", "expected a boolean, not a quantity in m"),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match Evaluator::new().eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("if 2 then 1 else 0"));
+        match Evaluator::new().eval(&parser.parse()?) {
+            Err(err @ Error::TypeMismatch { .. }) => {
+                assert_eq!(Span::new(3, 4, 1, 4), err.span());
+            }
+            res => panic!("expected a type mismatch, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("1 m < 1 s"));
+        assert!(matches!(
+            Evaluator::new().eval(&parser.parse()?),
+            Err(Error::IncompatibleUnits { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test13() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("10 - 2 * 5"));
-        let expected = 0.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test135() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("f(x, y) = x^2 + y", "f(x, y)"),
+            ("f(3, 4)", "13"),
+            ("two() = 2", "two()"),
+            ("f(two(), two())", "6"),
+            ("fact(n) = if n <= 1 then 1 else n * fact(n - 1)", "fact(n)"),
+            ("fact(10)", "3628800"),
+            ("fib(n) = n < 2 ? n : fib(n - 1) + fib(n - 2)", "fib(n)"),
+            ("fib(15)", "610"),
+            // Parameters hide global variables of the same name, and other
+            // names are looked up when the function is called.
+            ("x = 10", "10"),
+            ("g(x) = x + a", "g(x)"),
+            ("a = 1", "1"),
+            ("g(2)", "3"),
+            ("x", "10"),
+            ("later(d) = d + 1 day", "later(d)"),
+            ("later(@2026-10-18)", "2026-10-19"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        // A function does not see the parameters of its caller.
+        for input in ["outer(arg) = inner()", "inner() = arg"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            evaluator.eval(&parser.parse()?)?;
+        }
+        let mut parser = Parser::new(Lexer::new("outer(1)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::UndefinedVariable { name, .. }) if name == "arg"
+        ));
+
+        let mut parser = Parser::new(Lexer::new("f(x) = 1/x"));
+        let mut evaluator: Evaluator<Rational> = exact();
+        evaluator.eval(&parser.parse()?)?;
+        let mut parser = Parser::new(Lexer::new("f(3) + f(6)"));
+        assert_eq!(ratio(1, 2), evaluator.eval(&parser.parse()?)?);
 
         Ok(())
     }
 
     #[test]
-    fn test14() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("8 / 4 + 2"));
-        let expected = 4.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test136() -> eyre::Result<()> {
+        let mut parser: Parser = Parser::new(Lexer::new("f(x, y) = x^2 + y"));
+        match parser.parse()? {
+            ast @ Ast::FunctionDef { .. } => assert_eq!("f(x,y)=x^2+y", ast.to_string()),
+            ast => panic!("expected a function definition, got {ast:?}"),
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser: Parser = Parser::new(Lexer::new("f(x, 2) = x"));
+        match parser.parse() {
+            Err(parser::Error::InvalidAssignmentTarget { target, span }) => {
+                assert_eq!("2", target);
+                assert_eq!(Span::new(5, 6, 1, 6), span);
+            }
+            res => panic!("expected an invalid assignment target, got {res:?}"),
+        }
+
+        let mut parser: Parser = Parser::new(Lexer::new("f(x, x) = x"));
+        assert!(matches!(parser.parse(), Err(parser::Error::Parse { .. })));
+
+        // The body of a function is an expression, not an assignment.
+        let mut parser: Parser = Parser::new(Lexer::new("f(x) = y = x"));
+        assert!(matches!(
+            parser.parse(),
+            Err(parser::Error::UnexpectedToken { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test15() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("6 + 4 / 2"));
-        let expected = 8.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test137() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+        evaluator.options_mut().max_depth = 20;
 
-        assert_eq!(expected, actual);
+        for input in ["down(n) = n == 0 ? 0 : 1 + down(n - 1)", "inv(x) = 1 / x"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            evaluator.eval(&parser.parse()?)?;
+        }
+
+        let mut parser = Parser::new(Lexer::new("down(19)"));
+        assert_eq!(Value::Number(19.0), evaluator.eval(&parser.parse()?)?);
+
+        let mut parser = Parser::new(Lexer::new("down(20)"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(Error::RecursionLimit { name, limit, span }) => {
+                assert_eq!("down", name);
+                assert_eq!(20, limit);
+                assert_eq!(Span::new(0, 8, 1, 1), span);
+            }
+            res => panic!("expected a recursion limit error, got {res:?}"),
+        }
+        assert_eq!(0, evaluator.environment().depth());
+
+        // Errors in the body are reported at the call.
+        let mut parser = Parser::new(Lexer::new("1 + inv(0)"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(Error::DivisionByZero { span }) => assert_eq!(Span::new(4, 10, 1, 5), span),
+            res => panic!("expected division by zero, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("inv(1, 2)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::WrongArgumentCount { got: 2, .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test16() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("5 * 2 - 3"));
-        let expected = 7.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test138() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            ("f(x) = x", 0),
+            ("f(x) = 2 * x", 1),
+            ("sqrt(x) = x / 2", 1),
+            // g is also the gram.
+            ("g(y) = y", 1),
+        ];
+        for (input, warnings) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            evaluator.eval(&parser.parse()?)?;
+            let diagnostics = evaluator.take_diagnostics();
+            assert_eq!(warnings, diagnostics.len(), "for {input}");
+            for diagnostic in diagnostics {
+                assert_eq!(Severity::Warning, diagnostic.severity);
+            }
+        }
+
+        let mut parser = Parser::new(Lexer::new("sqrt(f(9))"));
+        assert_eq!(Value::Number(9.0), evaluator.eval(&parser.parse()?)?);
+
+        for input in ["pi(x) = x", "h(e) = e"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(matches!(
+                evaluator.eval(&parser.parse()?),
+                Err(Error::AssignToConstant { .. })
+            ));
+        }
+
+        let inputs = [
+            ("f + 1", "'+' is not defined for a function and a number"),
+            ("cos(g)", "cos is not defined for functions"),
+            ("if g then 1 else 0", "expected a boolean, not a function"),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test17() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(2 + 3) * 4 "));
-        let expected = 20.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test139() -> eyre::Result<()> {
+        let kinds = Lexer::new("x -> x - -1")
+            .map(|tok| tok.map(|tok| tok.kind))
+            .collect::<lexer::Result<Vec<_>>>()?;
+        let expected = [
+            TokenKind::Identifier,
+            TokenKind::Arrow,
+            TokenKind::Identifier,
+            TokenKind::Minus,
+            TokenKind::Minus,
+            TokenKind::Number,
+        ];
+        assert_eq!(expected.as_slice(), kinds);
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            ("x -> x^2", "x->x^2"),
+            ("(x) -> x", "x->x"),
+            ("(x, y) -> x + y", "(x,y)->x+y"),
+            ("() -> 1", "()->1"),
+            ("x -> y -> x * y", "x->y->x*y"),
+            ("map(x -> x + 1, 2)", "map(x->x+1,2)"),
+            ("sq = x -> x^2", "sq=x->x^2"),
+            ("(x + 1) * 2", "x+1*2"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, parser.parse()?.to_string(), "for {input}");
+        }
+
+        for input in ["(x, 1) -> x", "(x, x) -> x", "x ->", "1 -> 2"] {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert!(parser.parse().is_err(), "expected {input} not to parse");
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test18() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("10 / (5 - 3)"));
-        let expected = 5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test140() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("sq = x -> x^2", "lambda(x)"),
+            ("sq(4)", "16"),
+            ("() -> 1", "lambda()"),
+            ("adder(n) = x -> x + n", "adder(n)"),
+            ("add2 = adder(2)", "lambda(x)"),
+            ("add2(40)", "42"),
+            ("n", "n"),
+            ("curry = x -> y -> x * y", "lambda(x)"),
+            ("triple = curry(3)", "lambda(y)"),
+            ("triple(5)", "15"),
+            // Names other than parameters are looked up when called.
+            ("scale = x -> k * x", "lambda(x)"),
+            ("k = 10", "10"),
+            ("scale(2)", "20"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            let result = match evaluator.eval(&parser.parse()?) {
+                Err(Error::UndefinedVariable { name, .. }) => name,
+                res => res?.to_string(),
+            };
+            assert_eq!(expected, result, "for {input}");
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test19() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(8 + 2) * (3 - 1)"));
-        let expected = 20.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test141() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("map(x -> x^2, 1, 2, 3)", "[1, 4, 9]"),
+            ("map(x -> x^2)", "[]"),
+            ("filter(x -> x > 1, 1, 2, 3)", "[2, 3]"),
+            ("reduce((a, b) -> a * b, 1, 2, 3, 4)", "24"),
+            ("reduce((a, b) -> a * b, 5)", "5"),
+            (
+                "map(x -> x + 1, filter(x -> x mod 2 == 0, 1, 2, 3, 4))",
+                "[3, 5]",
+            ),
+            ("f(x) = 2 * x", "f(x)"),
+            ("map(f, 1 m, 2 s)", "[2 m, 4 s]"),
+            ("sum(k -> k, 1, 100)", "5050"),
+            ("sum(k -> k, -2, 2)", "0"),
+            ("sum(k -> k, 1, 0)", "0"),
+            ("product(k -> k, 1, 10)", "3628800"),
+            ("product(k -> k, 1, 0)", "1"),
+            ("sum(k -> k m, 1, 3)", "6 m"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("sum(k -> 1/k^2, 1, 1000)"));
+        match evaluator.eval(&parser.parse()?)? {
+            Value::Number(x) => assert!((x - 1.643_934_566_681_561_5).abs() < 1e-12),
+            value => panic!("expected a number, got {value:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("sum(k -> 1/k^2, 1, 3)"));
+        assert_eq!(ratio(49, 36), exact().eval(&parser.parse()?)?);
 
         Ok(())
     }
 
     #[test]
-    fn test20() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(6 - 2) * (3 + 1)"));
-        let expected = 16.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test142() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            ("map(3, 1)", "map expects a function first, not a number"),
+            ("filter(x -> x, 1)", "expected a boolean, not a number"),
+            (
+                "(x -> x) + 1",
+                "'+' is not defined for a function and a number",
+            ),
+            (
+                "map(x -> x, 1) * (x -> x)",
+                "'*' is not defined for a number and a function",
+            ),
+            ("sqrt(map(x -> x, 4))", "sqrt is not defined for lists"),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
+
+        let inputs = [
+            ("sum(k -> k, 1, 2.5)", Span::new(15, 18, 1, 16)),
+            ("product(k -> k, 1, 1e7)", Span::new(0, 23, 1, 1)),
+        ];
+        for (input, span) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::InvalidArgument { .. }) => assert_eq!(span, err.span()),
+                res => panic!("expected an invalid argument for {input}, got {res:?}"),
+            }
+        }
+
+        let inputs = [
+            ("reduce((a, b) -> a)", "reduce"),
+            ("sum(k -> k, 1)", "sum"),
+            ("map((a, b) -> a, 1)", "lambda"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(Error::WrongArgumentCount { name, .. }) => assert_eq!(expected, name),
+                res => panic!("expected a wrong argument count for {input}, got {res:?}"),
+            }
+        }
+
+        let mut parser = Parser::new(Lexer::new("e -> e"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::AssignToConstant { .. })
+        ));
+
+        let mut parser = Parser::new(Lexer::new("sum(f, a, b) = a + b"));
+        evaluator.eval(&parser.parse()?)?;
+        assert_eq!(1, evaluator.take_diagnostics().len());
+        let mut parser = Parser::new(Lexer::new("sum(1, 2, 3)"));
+        assert_eq!(Value::Number(5.0), evaluator.eval(&parser.parse()?)?);
 
         Ok(())
     }
 
     #[test]
-    fn test21() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("((2 + 3) * (4 - 1)) + 5 "));
-        let expected = 20.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test143() -> eyre::Result<()> {
+        let kinds = Lexer::new("[1..2]")
+            .map(|tok| tok.map(|tok| tok.kind))
+            .collect::<lexer::Result<Vec<_>>>()?;
+        let expected = [
+            TokenKind::LeftBracket,
+            TokenKind::Number,
+            TokenKind::DoubleDot,
+            TokenKind::Number,
+            TokenKind::RightBracket,
+        ];
+        assert_eq!(expected.as_slice(), kinds);
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            ("[1, 2, 3]", "[1,2,3]"),
+            ("[]", "[]"),
+            ("[[1], [2, 3]]", "[[1],[2,3]]"),
+            ("v[0]", "v[0]"),
+            ("v[i + 1][0]^2", "v[i+1][0]^2"),
+            ("-v[0]", "-v[0]"),
+            ("1..n + 1", "1..n+1"),
+            ("1..3 == [1, 2, 3]", "1..3==[1,2,3]"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, parser.parse()?.to_string(), "for {input}");
+        }
+
+        for input in ["[1, 2", "[1 2]", "v[0", "v[]", "1..2..3", "1 ]"] {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            assert!(parser.parse().is_err(), "expected {input} not to parse");
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test22() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("10 - (3 * (2 + 1)) "));
-        let expected = 1.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test144() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("[1, 2, 3] * 2", "[2, 4, 6]"),
+            ("[1, 2, 3] + [4, 5, 6]", "[5, 7, 9]"),
+            ("10 - [1, 2]", "[9, 8]"),
+            ("[1, 2] / [4, 8]", "[0.25, 0.25]"),
+            ("[1, 2]^2", "[1, 4]"),
+            ("-[1, -2]", "[-1, 2]"),
+            ("[[1, 2], [3, 4]] * 2", "[[2, 4], [6, 8]]"),
+            ("[1 m, 2 s] * 2", "[2 m, 4 s]"),
+            ("[] + 1", "[]"),
+            ("dot([1, 2, 3], [4, 5, 6])", "32"),
+            ("dot([], [])", "0"),
+            ("len([1, 2, 3])", "3"),
+            ("len([])", "0"),
+            ("v = [10, 20, 30]", "[10, 20, 30]"),
+            ("v[0]", "10"),
+            ("v[len(v) - 1]", "30"),
+            ("[[1, 2], [3, 4]][1][0]", "3"),
+            ("1..5", "[1, 2, 3, 4, 5]"),
+            ("-1..1", "[-1, 0, 1]"),
+            ("3..1", "[]"),
+            ("len(1..10)", "10"),
+            ("map(x -> x^2, 1..3)", "[1, 4, 9]"),
+            ("[1, 2] == [1, 2]", "true"),
+            ("[1, 2] == [1, 2, 3]", "false"),
+            ("[1, 2] != [2, 1]", "true"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("(1..3) / 3"));
+        assert_eq!(
+            Value::List(vec![ratio(1, 3), ratio(2, 3), ratio(1, 1)]),
+            exact().eval(&parser.parse()?)?
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test23() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(4 / (1 + 1)) * (3 + 5)"));
-        let expected = 16.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test145() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            (
+                "[1, 2] + [1, 2, 3]",
+                "'+' needs lists of the same length, got 2 and 3",
+            ),
+            (
+                "dot([1], [1, 2])",
+                "dot needs lists of the same length, got 1 and 2",
+            ),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::ShapeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a shape mismatch for {input}, got {res:?}"),
+            }
+        }
+
+        for (input, index) in [("[1, 2][2]", 2), ("[][0]", 0), ("[1][-1]", -1)] {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(Error::IndexOutOfRange { index: i, .. }) => assert_eq!(index, i),
+                res => panic!("expected an index out of range for {input}, got {res:?}"),
+            }
+        }
+
+        let inputs = [
+            ("5[0]", "only lists can be indexed, not a number"),
+            ("len(5)", "len expects a list, not a number"),
+            ("[1] < [2]", "'<' is not defined for a list and a list"),
+            (
+                "[true] + 1",
+                "'+' is not defined for a boolean and a number",
+            ),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
+
+        let inputs = [
+            ("[1, 2][0.5]", Span::new(7, 10, 1, 8)),
+            ("1..2.5", Span::new(3, 6, 1, 4)),
+            ("1..1e7", Span::new(0, 6, 1, 1)),
+        ];
+        for (input, span) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::InvalidArgument { .. }) => assert_eq!(span, err.span()),
+                res => panic!("expected an invalid argument for {input}, got {res:?}"),
+            }
+        }
+
+        let mut parser = Parser::new(Lexer::new("len(v) = 1"));
+        evaluator.eval(&parser.parse()?)?;
+        assert_eq!(1, evaluator.take_diagnostics().len());
 
         Ok(())
     }
 
     #[test]
-    fn test24() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("3 * (2 + (4 - 1)) "));
-        let expected = 15.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test146() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("a = [[1, 2], [3, 4]]", "[[1, 2], [3, 4]]"),
+            ("a * a", "[[7, 10], [15, 22]]"),
+            ("a * [[1], [1]]", "[[3], [7]]"),
+            ("a * [1, 1]", "[3, 7]"),
+            ("a + a", "[[2, 4], [6, 8]]"),
+            ("a - 1", "[[0, 1], [2, 3]]"),
+            ("2 * a", "[[2, 4], [6, 8]]"),
+            ("a / 2", "[[0.5, 1], [1.5, 2]]"),
+            ("-a", "[[-1, -2], [-3, -4]]"),
+            ("a[1]", "[3, 4]"),
+            ("a[1][0]", "3"),
+            ("a == [[1, 2], [3, 4]]", "true"),
+            ("a == [[1, 2]]", "false"),
+            ("a != transpose(a)", "true"),
+            ("[1..2, 3..4]", "[[1, 2], [3, 4]]"),
+            ("[[1, 2], 3]", "[[1, 2], 3]"),
+            ("[[[1]], [[2]]]", "[[[1]], [[2]]]"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("[[1, 2], 3]"));
+        assert!(matches!(evaluator.eval(&parser.parse()?)?, Value::List(_)));
+
+        for (input, start, end) in [("[[1, 2], [3]]", 0, 13), ("2 * [[1], [2, 3]]", 4, 17)] {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::IncompatibleShapes { .. }) => {
+                    assert_eq!(Span::new(start, end, 1, start + 1), err.span());
+                }
+                res => panic!("expected an incompatible shapes error for {input}, got {res:?}"),
+            }
+        }
+        let mut parser = Parser::new(Lexer::new("[[1, 2], [3]]"));
+        let err = evaluator.eval(&parser.parse()?).unwrap_err();
+        assert_eq!(
+            "a list of rows needs rows of the same length, got a list of 2 and a list of 1",
+            err.to_string()
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test25() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-(-3 + 5) "));
-        let expected = -2.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test147() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            (
+                "transpose([[1, 2, 3], [4, 5, 6]])",
+                "[[1, 4], [2, 5], [3, 6]]",
+            ),
+            ("det([[2, 1], [1, 3]])", "5"),
+            ("det([[0, 1], [1, 0]])", "-1"),
+            ("det([[1, 2], [2, 4]])", "0"),
+            ("det([[7]])", "7"),
+            ("inv([[2, 0], [0, 4]])", "[[0.5, 0], [0, 0.25]]"),
+            ("solve([[2, 0], [0, 4]], [2, 8])", "[1, 2]"),
+            ("solve([[2, 0], [0, 4]], [[2], [8]])", "[[1], [2]]"),
+            ("identity(2)", "[[1, 0], [0, 1]]"),
+            ("zeros(1, 3)", "[[0, 0, 0]]"),
+            ("det(identity(4))", "1"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut exact = exact();
+        let results = [
+            (
+                "a = [[1, 2, 3], [4, 5, 6], [7, 8, 10]]",
+                "[[1, 2, 3], [4, 5, 6], [7, 8, 10]]",
+            ),
+            ("det(a)", "-3"),
+            ("inv(a)", "[[-2/3, -4/3, 1], [-2/3, 11/3, -2], [1, -2, 1]]"),
+            ("inv(a) * a == identity(3)", "true"),
+            ("solve(a, [1, 2, 3])", "[-1/3, 2/3, 0]"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                exact.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test26() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-(3 + -2) "));
-        let expected = -1.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test148() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            (
+                "[[1, 2]] * [[1, 2]]",
+                "'*' needs as many columns on the left as rows on the right, \
+                 got a 1×2 matrix and a 1×2 matrix",
+            ),
+            (
+                "[[1, 2]] + [[1], [2]]",
+                "'+' needs matrices of the same size, got a 1×2 matrix and a 2×1 matrix",
+            ),
+            (
+                "solve(identity(2), [1, 2, 3])",
+                "solve needs as many rows in b as in A, got a 2×2 matrix and a list of 3",
+            ),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::IncompatibleShapes { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected incompatible shapes for {input}, got {res:?}"),
+            }
+        }
+
+        let inputs = [
+            ("det(5)", "det expects a matrix, not a number"),
+            ("det([[true]])", "det needs a matrix of numbers"),
+            (
+                "identity(2) ^ 2",
+                "'^' is not defined for a 2×2 matrix and a number",
+            ),
+            (
+                "1 / identity(2)",
+                "'/' is not defined for a number and a 2×2 matrix",
+            ),
+            ("sqrt(identity(2))", "sqrt is not defined for matrices"),
+        ];
+        for (input, message) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::TypeMismatch { .. }) => assert_eq!(message, err.to_string()),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
+
+        for input in ["inv([[1, 2], [2, 4]])", "solve([[0, 0], [0, 0]], [1, 1])"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(
+                matches!(
+                    evaluator.eval(&parser.parse()?),
+                    Err(Error::SingularMatrix { .. })
+                ),
+                "expected a singular matrix for {input}"
+            );
+        }
+
+        let inputs = [
+            ("det([[1, 2]])", Span::new(4, 12, 1, 5)),
+            ("zeros(2, 0)", Span::new(9, 10, 1, 10)),
+            ("identity(1e4)", Span::new(0, 13, 1, 1)),
+        ];
+        for (input, span) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(err @ Error::InvalidArgument { .. }) => assert_eq!(span, err.span()),
+                res => panic!("expected an invalid argument for {input}, got {res:?}"),
+            }
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test27() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("+(3 + -4)"));
-        let expected = -1.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test149() -> eyre::Result<()> {
+        let mut evaluator = exact();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("abs(-1/3)", ratio(1, 3)),
+            ("max(1/3, 1/2)", ratio(1, 2)),
+            ("min(1/3, 1/2, -2/7)", ratio(-2, 7)),
+            ("floor(-7/2)", ratio(-4, 1)),
+            ("ceil(-7/2)", ratio(-3, 1)),
+            ("round(5/2)", ratio(3, 1)),
+            ("round(-5/2)", ratio(-3, 1)),
+            ("round(7/3)", ratio(2, 1)),
+            ("sign(-2/3)", ratio(-1, 1)),
+            ("sign(0)", ratio(0, 1)),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(expected, evaluator.eval(&parser.parse()?)?, "{input}");
+        }
+
+        let mut parser = Parser::new(Lexer::new("sign(-2.5) + round(0.49999999999999994)"));
+        assert_eq!(
+            Value::Number(-1.0),
+            Evaluator::new().eval(&parser.parse()?)?
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test28() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-((2 + 3) * 4)"));
-        let expected = -20.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test150() -> eyre::Result<()> {
+        let mut evaluator = decimal(50, Rounding::HalfEven);
 
-        assert_eq!(expected, actual);
+        let results = [
+            (
+                "sqrt(2)",
+                "1.4142135623730950488016887242096980785696718753769",
+            ),
+            ("sqrt(1.44)", "1.2"),
+            (
+                "abs(-1/3)",
+                "0.33333333333333333333333333333333333333333333333333",
+            ),
+            ("round(2.5) + floor(-0.1)", "2"),
+            ("sqrt(-4)", "2i"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            let actual = evaluator.eval(&parser.parse()?)?;
+            assert_eq!(expected, actual.to_string(), "{input}");
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test29() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("+5"));
-        let expected = 5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test151() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::<i128>::default();
+        let big = (1i128 << 100) + 1;
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("abs(-(2^100 + 1))", big),
+            ("max(2^100 + 1, 1)", big),
+            ("min(2^100 + 1, 2^100 + 2)", big),
+            ("floor(2^100 + 1) + ceil(0) + round(0)", big),
+            ("sign(-(2^100 + 1))", -1),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                Value::Number(expected),
+                evaluator.eval(&parser.parse()?)?,
+                "{input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("hypot(2^60, 0)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::Arithmetic {
+                source: number::Error::Inexact,
+                ..
+            })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test30() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-5"));
-        let expected = -5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test152() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("a = [[1, 2, 3], [4, 5, 6], [7, 8, 9]]"));
+        evaluator.eval(&parser.parse()?)?;
+
+        let mut parser = Parser::new(Lexer::new("det(a)"));
+        assert_eq!(Value::Number(0.0), evaluator.eval(&parser.parse()?)?);
+        let mut parser = Parser::new(Lexer::new("det(a * 1e-20)"));
+        assert_eq!(Value::Number(0.0), evaluator.eval(&parser.parse()?)?);
+
+        for input in ["inv(a)", "solve(a, [1, 2, 3])", "inv(a / 3)"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(
+                matches!(
+                    evaluator.eval(&parser.parse()?),
+                    Err(Error::SingularMatrix { .. })
+                ),
+                "{input}"
+            );
+        }
+
+        // Small but not singular.
+        let mut parser = Parser::new(Lexer::new("det([[1e-20, 0], [0, 1e-20]])"));
+        assert_eq!(Value::Number(1e-40), evaluator.eval(&parser.parse()?)?);
 
         Ok(())
     }
+
     #[test]
-    fn test31() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("-(-5)"));
-        let expected = 5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test153() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
+        evaluator.constants_mut().add_physical();
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("c * 2 s to km"));
+        let actual = evaluator.eval(&parser.parse()?)?;
+        assert_eq!("599584.916 km", actual.to_string());
+
+        let results = [
+            ("72 km/h to m/s", "20 m/s"),
+            ("3 h + 30 min", "3.5 h"),
+            ("3h 20min * 4", "800 min"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("h_P * 1 Hz to J"));
+        let actual = evaluator.eval(&parser.parse()?)?.to_quantity().unwrap();
+        assert_eq!(
+            (6.626_070_15e-34, "J"),
+            (actual.value, actual.unit.to_string().as_str())
+        );
+
+        let units = Units::default();
+        for (name, _) in evaluator.constants().iter() {
+            assert!(!units.contains(name), "the constant {name} is a unit");
+        }
+
+        let mut parser = Parser::new(Lexer::new("G * 1 kg"));
+        evaluator.eval(&parser.parse()?)?;
+        assert!(evaluator.take_diagnostics().is_empty());
 
         Ok(())
     }
 
     #[test]
-    fn test32() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("+(-5)"));
-        let expected = -5.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test154() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("+[1, -2] + +(3 m) / 1 m + +2i"));
+        assert_eq!(
+            "[4+2i, 1+2i]",
+            evaluator.eval(&parser.parse()?)?.to_string()
+        );
+
+        let inputs = [
+            ("+true", "'+' is not defined for a boolean"),
+            ("+@2026-10-18", "'+' is not defined for a date"),
+            ("+[1, false]", "'+' is not defined for a boolean"),
+            ("+(x -> x)", "'+' is not defined for a function"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            match evaluator.eval(&parser.parse()?) {
+                Err(Error::TypeMismatch { message, .. }) => assert_eq!(expected, message),
+                res => panic!("expected a type mismatch for {input}, got {res:?}"),
+            }
+        }
 
         Ok(())
     }
 
     #[test]
-    fn test33() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("3 + (4 * 2) / (1 - 5)"));
-        let expected = 1.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test155() -> eyre::Result<()> {
+        let inputs = [
+            ("0x1g", "'g' is not a valid digit in base 16", 0, 4),
+            ("0b1z", "'z' is not a valid digit in base 2", 0, 4),
+            ("1e", "expected digits after the exponent", 0, 2),
+            ("2 * 2e", "expected digits after the exponent", 4, 6),
+            ("1.5e + 1", "expected digits after the exponent", 0, 4),
+            ("1e+x", "expected digits after the exponent", 0, 2),
+        ];
+        for (input, expected, start, end) in inputs {
+            let mut parser: Parser = Parser::new(Lexer::new(input));
+            match parser.parse() {
+                Err(parser::Error::Lex(lexer::Error::MalformedNumber(reason, span))) => {
+                    assert_eq!(expected, reason, "for {input}");
+                    assert_eq!(Span::new(start, end, 1, start + 1), span, "for {input}");
+                }
+                res => panic!("expected a malformed number error for {input}, got {res:?}"),
+            }
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("1eV to J"));
+        let actual = Evaluator::new()
+            .eval(&parser.parse()?)?
+            .to_quantity()
+            .unwrap();
+        assert_eq!(1.602_176_634e-19, actual.value);
 
         Ok(())
     }
 
     #[test]
-    fn test34() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(8 - 3) * (-2) "));
-        let expected = -10.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test156() -> eyre::Result<()> {
+        for input in ["(1/2)^30000", "(1/2)^30000 == 0", "2^30000"] {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert!(
+                matches!(
+                    exact().eval(&parser.parse()?),
+                    Err(Error::Arithmetic {
+                        source: number::Error::Overflow,
+                        ..
+                    })
+                ),
+                "expected an overflow for {input}"
+            );
+        }
 
-        assert_eq!(expected, actual);
+        let mut parser = Parser::new(Lexer::new("(-1)^(2^40 + 1)"));
+        assert_eq!(ratio(-1, 1), exact().eval(&parser.parse()?)?);
 
         Ok(())
     }
 
     #[test]
-    fn test35() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("(10 - (3 * 2 + 1))"));
-        let expected = 3.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test157() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let results = [
+            ("adder(n) = x -> x + n", "adder(n)"),
+            ("adder(2)(3)", "5"),
+            ("adder(2)(3)^2", "25"),
+            ("curry = a -> b -> c -> 100 * a + 10 * b + c", "lambda(a)"),
+            ("curry(1)(2)(3)", "123"),
+            ("fs = map(adder, 1, 2)", "[lambda(x), lambda(x)]"),
+            ("fs[1](10)", "12"),
+            // A lambda keeps the parameters of the calls it was created in,
+            ("pair(a) = b -> c -> [a, b, c]", "pair(a)"),
+            ("pair(1)(2)(3)", "[1, 2, 3]"),
+            ("k(n) = map(x -> y -> n + x + y, 10, 20)", "k(n)"),
+            ("k(1)[1](300)", "321"),
+            // but not those of their callers.
+            ("a = 1", "1"),
+            ("g() = x -> x + a", "g()"),
+            ("h(a) = g()(0)", "h(a)"),
+            ("h(50)", "1"),
+        ];
+        for (input, expected) in results {
+            let mut parser = Parser::new(Lexer::new(input));
+            assert_eq!(
+                expected,
+                evaluator.eval(&parser.parse()?)?.to_string(),
+                "for {input}"
+            );
+        }
+
+        let mut parser = Parser::new(Lexer::new("sin(1)(2)"));
+        match evaluator.eval(&parser.parse()?) {
+            Err(err @ Error::TypeMismatch { .. }) => {
+                assert_eq!("expected a function, not a number", err.to_string());
+                assert_eq!(Span::new(0, 6, 1, 1), err.span());
+            }
+            res => panic!("expected a type mismatch, got {res:?}"),
+        }
+
+        let mut parser = Parser::new(Lexer::new("adder(2)(3, 4)"));
+        assert!(matches!(
+            evaluator.eval(&parser.parse()?),
+            Err(Error::WrongArgumentCount { name, got: 2, .. }) if name == "adder(2)"
+        ));
+
+        let mut parser: Parser = Parser::new(Lexer::new("(2)(3)"));
+        assert!(matches!(
+            parser.parse(),
+            Err(parser::Error::UnexpectedToken { .. })
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn test36() -> eyre::Result<()> {
-        let mut parser = Parser::new(Lexer::new("6 + (8 - (2 * 3)) * 4 "));
-        let expected = 14.0;
-        let actual = Evaluator::eval(parser.parse()?);
+    fn test158() -> eyre::Result<()> {
+        let mut evaluator = Evaluator::new();
 
-        assert_eq!(expected, actual);
+        let inputs = [
+            ("m = 3", Some("'m' shadows a unit")),
+            ("m = 4", None),
+            ("2 m", None),
+            ("i = 2", Some("'i' shadows the imaginary unit")),
+            ("km(x) = x", Some("'km' shadows a unit")),
+            ("x = 1", None),
+        ];
+        for (input, warning) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            evaluator.eval(&parser.parse()?)?;
+            let diagnostics = evaluator.take_diagnostics();
+            assert_eq!(
+                warning,
+                diagnostics.first().map(|d| d.message.as_str()),
+                "for {input}"
+            );
+            assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        }
+
+        let mut parser = Parser::new(Lexer::new("2 m + i"));
+        assert_eq!(Value::Number(10.0), evaluator.eval(&parser.parse()?)?);
 
         Ok(())
     }
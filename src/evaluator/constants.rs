@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+/// A unit as powers of unit symbols, such as `[("m", 1), ("s", -1)]`.
+pub type UnitPowers = &'static [(&'static str, i32)];
+
+/// A named, read-only value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Constant {
+    pub value: f64,
+    /// The unit of the value, which is empty for a plain number.
+    pub unit: UnitPowers,
+    pub description: &'static str,
+}
+
+const MATHEMATICAL: &[(&str, f64, &str)] = &[
+    (
+        "pi",
+        std::f64::consts::PI,
+        "ratio of a circle's circumference to its diameter",
+    ),
+    ("e", std::f64::consts::E, "base of the natural logarithm"),
+    (
+        "tau",
+        std::f64::consts::TAU,
+        "ratio of a circle's circumference to its radius",
+    ),
+    ("phi", 1.618_033_988_749_895, "golden ratio"),
+    ("inf", f64::INFINITY, "positive infinity"),
+    ("nan", f64::NAN, "not a number"),
+];
+
+/// CODATA 2018 values, in SI units. None takes the symbol of a unit, so
+/// the Planck constant is `h_P` and `h` stays the hour.
+const PHYSICAL: &[(&str, f64, UnitPowers, &str)] = &[
+    (
+        "c",
+        299_792_458.0,
+        &[("m", 1), ("s", -1)],
+        "speed of light in vacuum, m/s",
+    ),
+    (
+        "h_P",
+        6.626_070_15e-34,
+        &[("J", 1), ("s", 1)],
+        "Planck constant, J s",
+    ),
+    (
+        "G",
+        6.674_30e-11,
+        &[("m", 3), ("kg", -1), ("s", -2)],
+        "Newtonian constant of gravitation, m^3/(kg s^2)",
+    ),
+    (
+        "k_B",
+        1.380_649e-23,
+        &[("J", 1), ("K", -1)],
+        "Boltzmann constant, J/K",
+    ),
+    (
+        "N_A",
+        6.022_140_76e23,
+        &[("mol", -1)],
+        "Avogadro constant, 1/mol",
+    ),
+];
+
+/// The constants known to an evaluator. Names are resolved against these
+/// before variables, and cannot be assigned to, except for the symbols of
+/// units: a unit always wins over a constant of the same name.
+#[derive(Debug, Clone)]
+pub struct Constants {
+    constants: BTreeMap<String, Constant>,
+}
+
+impl Constants {
+    /// A table without any constants, not even the mathematical ones.
+    pub fn empty() -> Self {
+        Self {
+            constants: BTreeMap::new(),
+        }
+    }
+
+    /// The mathematical constants together with the physical ones.
+    pub fn with_physical() -> Self {
+        let mut constants = Constants::default();
+        constants.add_physical();
+        constants
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Constant> {
+        self.constants.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.constants.contains_key(name)
+    }
+
+    /// Defines `name` as a constant, replacing any constant already there.
+    pub fn define(&mut self, name: impl Into<String>, value: f64, description: &'static str) {
+        self.constants.insert(
+            name.into(),
+            Constant {
+                value,
+                unit: &[],
+                description,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Constant> {
+        self.constants.remove(name)
+    }
+
+    pub fn add_physical(&mut self) {
+        for &(name, value, unit, description) in PHYSICAL {
+            self.constants.insert(
+                name.to_owned(),
+                Constant {
+                    value,
+                    unit,
+                    description,
+                },
+            );
+        }
+    }
+
+    pub fn remove_physical(&mut self) {
+        for &(name, ..) in PHYSICAL {
+            self.remove(name);
+        }
+    }
+
+    pub fn has_physical(&self) -> bool {
+        PHYSICAL.iter().all(|&(name, ..)| self.contains(name))
+    }
+
+    /// The constants in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Constant)> {
+        self.constants.iter().map(|(name, c)| (name.as_str(), c))
+    }
+}
+
+impl Default for Constants {
+    /// A table holding the mathematical constants.
+    fn default() -> Self {
+        let mut constants = Constants::empty();
+        for &(name, value, description) in MATHEMATICAL {
+            constants.define(name, value, description);
+        }
+        constants
+    }
+}
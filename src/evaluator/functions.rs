@@ -0,0 +1,175 @@
+use std::{collections::HashMap, fmt};
+
+use crate::number::complex::Complex;
+
+/// How many arguments a function takes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, n: usize) -> bool {
+        match *self {
+            Arity::Exact(expected) => n == expected,
+            Arity::AtLeast(min) => n >= min,
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, n) = match *self {
+            Arity::Exact(n) => ("", n),
+            Arity::AtLeast(n) => ("at least ", n),
+        };
+        write!(f, "{prefix}{n} argument{}", if n == 1 { "" } else { "s" })
+    }
+}
+
+/// A function that can be called by name from an expression. The evaluator
+/// checks the number of arguments against `arity` before calling it.
+///
+/// A function with a `complex` version is called with that instead when any
+/// argument is complex, or when `call` has no real result, as for `sqrt(-1)`.
+#[derive(Debug, Copy, Clone)]
+pub struct Function {
+    pub arity: Arity,
+    pub call: fn(&[f64]) -> f64,
+    pub complex: Option<fn(&[Complex]) -> Complex>,
+}
+
+/// The functions known to an evaluator, keyed by name.
+#[derive(Debug, Clone)]
+pub struct Functions {
+    functions: HashMap<String, Function>,
+}
+
+impl Functions {
+    /// An empty registry, without even the built-in functions.
+    pub fn empty() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    /// Registers `call` under `name`, replacing any function already there.
+    pub fn register(&mut self, name: impl Into<String>, arity: Arity, call: fn(&[f64]) -> f64) {
+        self.functions.insert(
+            name.into(),
+            Function {
+                arity,
+                call,
+                complex: None,
+            },
+        );
+    }
+
+    /// Registers a function with versions for real and complex arguments.
+    pub fn register_complex(
+        &mut self,
+        name: impl Into<String>,
+        arity: Arity,
+        call: fn(&[f64]) -> f64,
+        complex: fn(&[Complex]) -> Complex,
+    ) {
+        self.functions.insert(
+            name.into(),
+            Function {
+                arity,
+                call,
+                complex: Some(complex),
+            },
+        );
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+}
+
+impl Default for Functions {
+    /// A registry holding the built-in functions.
+    fn default() -> Self {
+        use Arity::*;
+
+        let mut functions = Functions::empty();
+
+        functions.register_complex("sin", Exact(1), |args| args[0].sin(), |args| args[0].sin());
+        functions.register_complex("cos", Exact(1), |args| args[0].cos(), |args| args[0].cos());
+        functions.register_complex("tan", Exact(1), |args| args[0].tan(), |args| args[0].tan());
+        functions.register("asin", Exact(1), |args| args[0].asin());
+        functions.register("acos", Exact(1), |args| args[0].acos());
+        functions.register("atan", Exact(1), |args| args[0].atan());
+        functions.register("atan2", Exact(2), |args| args[0].atan2(args[1]));
+        functions.register_complex(
+            "sqrt",
+            Exact(1),
+            |args| args[0].sqrt(),
+            |args| args[0].sqrt(),
+        );
+        functions.register("cbrt", Exact(1), |args| args[0].cbrt());
+        functions.register_complex("exp", Exact(1), |args| args[0].exp(), |args| args[0].exp());
+        functions.register_complex("ln", Exact(1), |args| args[0].ln(), |args| args[0].ln());
+        functions.register_complex(
+            "log10",
+            Exact(1),
+            |args| args[0].log10(),
+            |args| args[0].ln() / Complex::from(std::f64::consts::LN_10),
+        );
+        functions.register_complex(
+            "log2",
+            Exact(1),
+            |args| args[0].log2(),
+            |args| args[0].ln() / Complex::from(std::f64::consts::LN_2),
+        );
+        functions.register("log", Exact(2), |args| args[0].log(args[1]));
+        functions.register_complex(
+            "abs",
+            Exact(1),
+            |args| args[0].abs(),
+            |args| Complex::from(args[0].abs()),
+        );
+        functions.register_complex(
+            "arg",
+            Exact(1),
+            |args| 0f64.atan2(args[0]),
+            |args| Complex::from(args[0].arg()),
+        );
+        functions.register_complex("conj", Exact(1), |args| args[0], |args| args[0].conj());
+        functions.register_complex(
+            "re",
+            Exact(1),
+            |args| args[0],
+            |args| Complex::from(args[0].re),
+        );
+        functions.register_complex("im", Exact(1), |_| 0.0, |args| Complex::from(args[0].im));
+        functions.register("floor", Exact(1), |args| args[0].floor());
+        functions.register("ceil", Exact(1), |args| args[0].ceil());
+        functions.register("round", Exact(1), |args| args[0].round());
+        functions.register("sign", Exact(1), |args| {
+            if args[0] == 0.0 {
+                args[0]
+            } else {
+                args[0].signum()
+            }
+        });
+        functions.register("min", AtLeast(1), |args| {
+            args.iter().copied().fold(f64::INFINITY, f64::min)
+        });
+        functions.register("max", AtLeast(1), |args| {
+            args.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        });
+        functions.register("hypot", Exact(2), |args| args[0].hypot(args[1]));
+        // Arguments arrive as f64, so this turns an exact fraction back into
+        // a decimal.
+        functions.register("float", Exact(1), |args| args[0]);
+
+        functions
+    }
+}
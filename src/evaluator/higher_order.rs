@@ -0,0 +1,151 @@
+use crate::{
+    ast::{Ast, Op},
+    number::Numeric,
+    span::Span,
+    value::Value,
+};
+
+use super::{Arity, Error, Evaluator, Result};
+
+/// The built-in functions that take a function as their first argument,
+/// and how many arguments each takes.
+pub(super) const HIGHER_ORDER: &[(&str, Arity)] = &[
+    ("map", Arity::AtLeast(1)),
+    ("filter", Arity::AtLeast(1)),
+    ("reduce", Arity::AtLeast(2)),
+    ("sum", Arity::Exact(3)),
+    ("product", Arity::Exact(3)),
+];
+
+/// The most terms that `sum` and `product` will evaluate, and the most
+/// elements of a range.
+pub(super) const MAX_TERMS: f64 = 1e6;
+
+impl<N: Numeric> Evaluator<N> {
+    /// Calls the higher-order built-in `name`, one of:
+    ///
+    /// - `map(f, xs...)`, the list of `f(x)` for each `x`;
+    /// - `filter(p, xs...)`, the list of each `x` for which `p(x)` is true;
+    /// - `reduce(f, xs...)`, the `xs` combined from the left, as in
+    ///   `f(f(x1, x2), x3)`;
+    /// - `sum(f, a, b)` and `product(f, a, b)`, the sum or product of `f(k)`
+    ///   for each whole `k` from `a` to `b`.
+    ///
+    /// The `xs` may also be given as a single list.
+    pub(super) fn eval_higher_order(
+        &mut self,
+        name: &str,
+        arity: Arity,
+        args: &[Ast<N>],
+        span: Span,
+    ) -> Result<Value<N>> {
+        if !arity.accepts(args.len()) {
+            return Err(Error::WrongArgumentCount {
+                name: name.to_owned(),
+                expected: arity,
+                got: args.len(),
+                span,
+            });
+        }
+        let function = match self.eval(&args[0])? {
+            Value::Function(function) => function,
+            value => {
+                return Err(Error::TypeMismatch {
+                    message: format!(
+                        "{name} expects a function first, not {}",
+                        Self::describe(&value)
+                    ),
+                    span: args[0].span(),
+                })
+            }
+        };
+        let values = args[1..]
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let values = match values.as_slice() {
+            [Value::List(list)] => list.clone(),
+            _ => values,
+        };
+        let function_name = function.name.as_deref().unwrap_or("lambda");
+
+        match name {
+            "map" => values
+                .into_iter()
+                .map(|value| self.call(function_name, &function, vec![value], span))
+                .collect::<Result<_>>()
+                .map(Value::List),
+            "filter" => {
+                let mut kept = Vec::new();
+                for value in values {
+                    match self.call(function_name, &function, vec![value.clone()], span)? {
+                        Value::Bool(true) => kept.push(value),
+                        Value::Bool(false) => {}
+                        result => {
+                            return Err(Error::TypeMismatch {
+                                message: format!(
+                                    "expected a boolean, not {}",
+                                    Self::describe(&result)
+                                ),
+                                span: args[0].span(),
+                            })
+                        }
+                    }
+                }
+                Ok(Value::List(kept))
+            }
+            "reduce" => {
+                let mut values = values.into_iter();
+                let first = values.next().ok_or_else(|| Error::InvalidArgument {
+                    message: "reduce needs at least one value".to_owned(),
+                    span,
+                })?;
+                values.try_fold(first, |acc, value| {
+                    self.call(function_name, &function, vec![acc, value], span)
+                })
+            }
+            _ => {
+                let (op, empty) = if name == "sum" {
+                    (Op::Add, "0")
+                } else {
+                    (Op::Mul, "1")
+                };
+                // Numbers in `N`, so that an exact sum stays exact.
+                let number = |spelling| {
+                    N::from_spelling(spelling)
+                        .map(Value::Number)
+                        .map_err(|source| Error::Arithmetic { source, span })
+                };
+                let bound = |i: usize| {
+                    values[i]
+                        .to_f64()
+                        .filter(|x| x.fract() == 0.0)
+                        .ok_or_else(|| Error::InvalidArgument {
+                            message: format!("the bounds of {name} must be whole numbers"),
+                            span: args[i + 1].span(),
+                        })
+                };
+                let (from, to) = (bound(0)?, bound(1)?);
+                if to - from >= MAX_TERMS {
+                    return Err(Error::InvalidArgument {
+                        message: format!("{name} can take at most {MAX_TERMS} terms"),
+                        span,
+                    });
+                }
+
+                let one = number("1")?;
+                let mut k = values[0].clone();
+                let mut result = None;
+                for _ in 0..(to - from + 1.0).max(0.0) as u64 {
+                    let term = self.call(function_name, &function, vec![k.clone()], span)?;
+                    result = Some(match result {
+                        None => term,
+                        Some(result) => self.binary(&result, op, &term, span)?,
+                    });
+                    k = self.binary(&k, Op::Add, &one, span)?;
+                }
+                result.map_or_else(|| number(empty), Ok)
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    ast::{Ast, Op},
+    number::Numeric,
+    span::Span,
+    value::{Matrix, Value},
+};
+
+use super::{higher_order::MAX_TERMS, Arity, Error, Evaluator, Result};
+
+/// The built-in functions of lists, and how many arguments each takes.
+pub(super) const LISTS: &[(&str, Arity)] = &[("len", Arity::Exact(1)), ("dot", Arity::Exact(2))];
+
+impl<N: Numeric> Evaluator<N> {
+    /// Evaluates a list literal, which is a matrix if its elements are rows:
+    /// lists of the same length whose elements are not lists themselves.
+    /// Lists of lists must have the same length too.
+    pub(super) fn eval_list(&mut self, elements: &[Ast<N>], span: Span) -> Result<Value<N>> {
+        let values = elements
+            .iter()
+            .map(|element| self.eval(element))
+            .collect::<Result<Vec<_>>>()?;
+        let lengths = values
+            .iter()
+            .map(|value| match value {
+                Value::List(list) => Some(list.len()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(lengths) = lengths {
+            if let Some(i) = lengths.iter().position(|&n| n != lengths[0]) {
+                return Err(Self::incompatible_shapes(
+                    "a list of rows".to_owned(),
+                    "rows of the same length",
+                    &values[0],
+                    &values[i],
+                    span,
+                ));
+            }
+        }
+        let is_row = |value: &Value<N>| match value {
+            Value::List(row) => !row
+                .iter()
+                .any(|value| matches!(value, Value::List(_) | Value::Matrix(_))),
+            _ => false,
+        };
+        if !values.iter().all(is_row) {
+            return Ok(Value::List(values));
+        }
+
+        let rows = values
+            .iter()
+            .map(|value| match value {
+                Value::List(row) => row.clone(),
+                _ => unreachable!("the rows are lists"),
+            })
+            .collect();
+        Ok(Matrix::from_rows(rows).map_or(Value::List(values), Value::Matrix))
+    }
+
+    /// Calls the list built-in `name`, one of:
+    ///
+    /// - `len(v)`, the number of elements of `v`;
+    /// - `dot(a, b)`, the sum of the products of the elements of `a` and `b`
+    ///   taken in pairs.
+    pub(super) fn eval_list_function(
+        &mut self,
+        name: &str,
+        arity: Arity,
+        args: &[Ast<N>],
+        span: Span,
+    ) -> Result<Value<N>> {
+        if !arity.accepts(args.len()) {
+            return Err(Error::WrongArgumentCount {
+                name: name.to_owned(),
+                expected: arity,
+                got: args.len(),
+                span,
+            });
+        }
+        let mut lists = Vec::with_capacity(args.len());
+        for arg in args {
+            match self.eval(arg)? {
+                Value::List(list) => lists.push(list),
+                value => {
+                    return Err(Error::TypeMismatch {
+                        message: format!("{name} expects a list, not {}", Self::describe(&value)),
+                        span: arg.span(),
+                    })
+                }
+            }
+        }
+
+        match (name, lists.as_slice()) {
+            ("dot", [a, b]) => {
+                if a.len() != b.len() {
+                    return Err(Error::ShapeMismatch {
+                        name: name.to_owned(),
+                        lhs: a.len(),
+                        rhs: b.len(),
+                        span,
+                    });
+                }
+                self.sum_of_products(a.iter().zip(b), span)
+            }
+            (_, [v]) => Self::count(v.len(), span),
+            _ => unreachable!("the arity is checked above"),
+        }
+    }
+
+    /// The sum of the products of `pairs`, which is zero if there are none.
+    pub(super) fn sum_of_products<'a>(
+        &self,
+        pairs: impl Iterator<Item = (&'a Value<N>, &'a Value<N>)>,
+        span: Span,
+    ) -> Result<Value<N>>
+    where
+        N: 'a,
+    {
+        let mut result = None;
+        for (x, y) in pairs {
+            let term = self.binary(x, Op::Mul, y, span)?;
+            result = Some(match result {
+                None => term,
+                Some(result) => self.binary(&result, Op::Add, &term, span)?,
+            });
+        }
+        result.map_or_else(|| Self::count(0, span), Ok)
+    }
+
+    /// Evaluates `value[index]`, counting from zero. The elements of a
+    /// matrix are its rows.
+    pub(super) fn eval_index(
+        &mut self,
+        value: &Ast<N>,
+        index: &Ast<N>,
+        span: Span,
+    ) -> Result<Value<N>> {
+        let list = match self.eval(value)? {
+            Value::List(list) => list,
+            Value::Matrix(matrix) => matrix
+                .iter_rows()
+                .map(|row| Value::List(row.to_vec()))
+                .collect(),
+            other => {
+                return Err(Error::TypeMismatch {
+                    message: format!("only lists can be indexed, not {}", Self::describe(&other)),
+                    span: value.span(),
+                })
+            }
+        };
+        let i = self
+            .eval(index)?
+            .to_f64()
+            .filter(|i| i.fract() == 0.0)
+            .ok_or_else(|| Error::InvalidArgument {
+                message: "an index must be a whole number".to_owned(),
+                span: index.span(),
+            })?;
+
+        if i < 0.0 || i >= list.len() as f64 {
+            return Err(Error::IndexOutOfRange {
+                index: i as i64,
+                len: list.len(),
+                span,
+            });
+        }
+        Ok(list
+            .into_iter()
+            .nth(i as usize)
+            .expect("the index is in range"))
+    }
+
+    /// Evaluates `from..to`, the list of `from`, `from + 1` and so on up to
+    /// and including `to`, which is empty if `to` is less than `from`.
+    pub(super) fn eval_range(
+        &mut self,
+        from: &Ast<N>,
+        to: &Ast<N>,
+        span: Span,
+    ) -> Result<Value<N>> {
+        let start = self.eval(from)?;
+        let end = self.eval(to)?;
+        let bound = |value: &Value<N>, ast: &Ast<N>| {
+            value
+                .to_f64()
+                .filter(|x| x.fract() == 0.0)
+                .ok_or_else(|| Error::InvalidArgument {
+                    message: "the bounds of a range must be whole numbers".to_owned(),
+                    span: ast.span(),
+                })
+        };
+        let (a, b) = (bound(&start, from)?, bound(&end, to)?);
+        if b - a >= MAX_TERMS {
+            return Err(Error::InvalidArgument {
+                message: format!("a range can have at most {MAX_TERMS} elements"),
+                span,
+            });
+        }
+
+        // Stepping in `N`, so that an exact range stays exact.
+        let one = Self::count(1, span)?;
+        let mut k = start;
+        let mut elements = Vec::new();
+        for _ in 0..(b - a + 1.0).max(0.0) as u64 {
+            let next = self.binary(&k, Op::Add, &one, span)?;
+            elements.push(std::mem::replace(&mut k, next));
+        }
+        Ok(Value::List(elements))
+    }
+
+    /// The whole number `n` as a number of type `N`.
+    pub(super) fn count(n: usize, span: Span) -> Result<Value<N>> {
+        N::from_spelling(&n.to_string())
+            .map(Value::Number)
+            .map_err(|source| Error::Arithmetic { source, span })
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{
+    ast::{Ast, Op},
+    number::Numeric,
+    span::Span,
+    value::{Matrix, Value},
+};
+
+use super::{higher_order::MAX_TERMS, Arity, Error, Evaluator, Result};
+
+/// The built-in functions of matrices, and how many arguments each takes.
+pub(super) const MATRICES: &[(&str, Arity)] = &[
+    ("transpose", Arity::Exact(1)),
+    ("det", Arity::Exact(1)),
+    ("inv", Arity::Exact(1)),
+    ("solve", Arity::Exact(2)),
+    ("identity", Arity::Exact(1)),
+    ("zeros", Arity::Exact(2)),
+];
+
+impl<N: Numeric> Evaluator<N> {
+    /// Calls the matrix built-in `name`, one of:
+    ///
+    /// - `transpose(A)`, `A` with its rows as columns;
+    /// - `det(A)` and `inv(A)`, the determinant and inverse of a square
+    ///   matrix;
+    /// - `solve(A, b)`, the `x` for which `A * x` is `b`, where `b` is a list
+    ///   or a matrix;
+    /// - `identity(n)`, the `n`×`n` identity matrix;
+    /// - `zeros(m, n)`, the `m`×`n` matrix of zeros.
+    pub(super) fn eval_matrix_function(
+        &mut self,
+        name: &str,
+        arity: Arity,
+        args: &[Ast<N>],
+        span: Span,
+    ) -> Result<Value<N>> {
+        if !arity.accepts(args.len()) {
+            return Err(Error::WrongArgumentCount {
+                name: name.to_owned(),
+                expected: arity,
+                got: args.len(),
+                span,
+            });
+        }
+        let values = args
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Result<Vec<_>>>()?;
+        if matches!(name, "identity" | "zeros") {
+            let sizes = values
+                .iter()
+                .zip(args)
+                .map(|(value, arg)| {
+                    value
+                        .to_f64()
+                        .filter(|n| n.fract() == 0.0 && *n >= 1.0)
+                        .ok_or_else(|| Error::InvalidArgument {
+                            message: "the size of a matrix must be a whole number of at least 1"
+                                .to_owned(),
+                            span: arg.span(),
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // `identity(n)` is square.
+            let (rows, cols) = (sizes[0], *sizes.last().expect("there is a size"));
+            if rows * cols > MAX_TERMS {
+                return Err(Error::InvalidArgument {
+                    message: format!("a matrix can have at most {MAX_TERMS} elements"),
+                    span,
+                });
+            }
+            return Self::constant_matrix(name, rows as usize, cols as usize, span)
+                .map(Value::Matrix);
+        }
+
+        let a = match &values[0] {
+            Value::Matrix(a) => a,
+            value => {
+                return Err(Error::TypeMismatch {
+                    message: format!("{name} expects a matrix, not {}", Self::describe(value)),
+                    span: args[0].span(),
+                })
+            }
+        };
+        if name == "transpose" {
+            return Ok(Value::Matrix(a.transpose()));
+        }
+        if a.rows() != a.cols() {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "{name} needs a square matrix, not {}",
+                    Self::describe(&values[0])
+                ),
+                span: args[0].span(),
+            });
+        }
+        if a.elements()
+            .iter()
+            .any(|value| value.to_complex().is_none())
+        {
+            return Err(Error::TypeMismatch {
+                message: format!("{name} needs a matrix of numbers"),
+                span: args[0].span(),
+            });
+        }
+
+        // The columns to the right of `a` that the elimination turns into
+        // the result, and whether that is a list.
+        let n = a.rows();
+        let (right, is_list) = match (name, values.get(1)) {
+            ("det", _) => (vec![Vec::new(); n], false),
+            ("inv", _) => {
+                let identity = Self::constant_matrix("identity", n, n, span)?;
+                (identity.iter_rows().map(<[_]>::to_vec).collect(), false)
+            }
+            (_, Some(Value::List(b))) if b.len() == n => {
+                (b.iter().map(|value| vec![value.clone()]).collect(), true)
+            }
+            (_, Some(Value::Matrix(b))) if b.rows() == n => {
+                (b.iter_rows().map(<[_]>::to_vec).collect(), false)
+            }
+            (_, Some(b @ (Value::List(_) | Value::Matrix(_)))) => {
+                return Err(Self::incompatible_shapes(
+                    name.to_owned(),
+                    "as many rows in b as in A",
+                    &values[0],
+                    b,
+                    span,
+                ))
+            }
+            (_, Some(b)) => {
+                return Err(Error::TypeMismatch {
+                    message: format!(
+                        "{name} expects a list or a matrix second, not {}",
+                        Self::describe(b)
+                    ),
+                    span: args[1].span(),
+                })
+            }
+            (_, None) => unreachable!("the arity is checked above"),
+        };
+
+        let mut rows = a
+            .iter_rows()
+            .zip(right)
+            .map(|(row, right)| row.iter().cloned().chain(right).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let det = self.eliminate(&mut rows, span)?;
+        match (name, det) {
+            ("det", det) => det.map_or_else(|| Self::count(0, span), Ok),
+            (_, None) => Err(Error::SingularMatrix {
+                name: name.to_owned(),
+                span: args[0].span(),
+            }),
+            (_, Some(_)) => {
+                let rows = rows
+                    .into_iter()
+                    .map(|row| row.into_iter().skip(n).collect())
+                    .collect::<Vec<Vec<_>>>();
+                Ok(if is_list {
+                    Value::List(rows.into_iter().flatten().collect())
+                } else {
+                    Value::Matrix(Matrix::from_rows(rows).expect("the result has columns"))
+                })
+            }
+        }
+    }
+
+    /// The `rows`×`cols` identity matrix if `name` is `identity`, and
+    /// otherwise the matrix of zeros.
+    fn constant_matrix(name: &str, rows: usize, cols: usize, span: Span) -> Result<Matrix<N>> {
+        let (zero, one) = (Self::count(0, span)?, Self::count(1, span)?);
+        let elements = (0..rows * cols)
+            .map(|i| {
+                if name == "identity" && i / cols == i % cols {
+                    one.clone()
+                } else {
+                    zero.clone()
+                }
+            })
+            .collect();
+        Ok(Matrix::new(rows, cols, elements))
+    }
+
+    /// Gauss-Jordan elimination of `rows`, whose leading columns are a
+    /// square matrix: row operations reduce that matrix to the identity
+    /// matrix, and apply to the columns after it too. Returns the
+    /// determinant of the square matrix, or `None` if it is singular, which
+    /// leaves the reduction unfinished.
+    ///
+    /// With rounded arithmetic a singular matrix rarely leaves a pivot of
+    /// exactly zero, so a pivot counts as zero up to the rounding error that
+    /// the elimination can build up, `n × ε × max |a_ij|`.
+    fn eliminate(&self, rows: &mut [Vec<Value<N>>], span: Span) -> Result<Option<Value<N>>> {
+        let magnitude = |value: &Value<N>| value.to_complex().map_or(0.0, |z| z.abs());
+        let epsilon = |value: &Value<N>| match value {
+            Value::Float(_) | Value::Complex(_) => f64::EPSILON,
+            _ => N::epsilon(&self.context),
+        };
+        let n = rows.len();
+        let (largest, epsilon) = rows.iter().flat_map(|row| &row[..n]).fold(
+            (0.0, 0.0),
+            |(largest, eps): (f64, f64), value| {
+                (largest.max(magnitude(value)), eps.max(epsilon(value)))
+            },
+        );
+        let tolerance = n as f64 * epsilon * largest;
+        let mut det = Self::count(1, span)?;
+
+        for col in 0..n {
+            // The largest pivot, which keeps rounding errors small.
+            let pivot = (col..n)
+                .max_by(|&i, &j| magnitude(&rows[i][col]).total_cmp(&magnitude(&rows[j][col])))
+                .expect("the column has rows left");
+            if magnitude(&rows[pivot][col]) <= tolerance {
+                return Ok(None);
+            }
+            if pivot != col {
+                rows.swap(pivot, col);
+                det = self.unary(Op::Sub, det, span)?;
+            }
+
+            let p = rows[col][col].clone();
+            det = self.binary(&det, Op::Mul, &p, span)?;
+            for value in &mut rows[col][col..] {
+                *value = self.binary(value, Op::Div, &p, span)?;
+            }
+            let pivot_row = rows[col].clone();
+            for (i, row) in rows.iter_mut().enumerate() {
+                if i == col || magnitude(&row[col]) == 0.0 {
+                    continue;
+                }
+                let factor = row[col].clone();
+                for (value, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    let term = self.binary(&factor, Op::Mul, p, span)?;
+                    *value = self.binary(value, Op::Sub, &term, span)?;
+                }
+            }
+        }
+        Ok(Some(det))
+    }
+
+    /// Arithmetic where either operand is a matrix. `*` of two matrices is
+    /// their product, as is `*` of a matrix and a list, which is a column.
+    /// Otherwise operators apply element by element, to matrices of the same
+    /// size or to a matrix and a number.
+    pub(super) fn eval_matrix(
+        &self,
+        l: &Value<N>,
+        op: Op,
+        r: &Value<N>,
+        span: Span,
+    ) -> Result<Value<N>> {
+        let is_collection = |v: &Value<N>| matches!(v, Value::List(_) | Value::Matrix(_));
+        let each = |matrix: &Matrix<N>, f: &dyn Fn(&Value<N>) -> Result<Value<N>>| {
+            let elements = matrix.elements().iter().map(f).collect::<Result<_>>()?;
+            Ok(Value::Matrix(Matrix::new(
+                matrix.rows(),
+                matrix.cols(),
+                elements,
+            )))
+        };
+        let operator = || format!("'{}'", op.to_string().trim());
+
+        match (l, op, r) {
+            (Value::Matrix(a), Op::Mul, Value::Matrix(b)) => {
+                if a.cols() != b.rows() {
+                    return Err(Self::incompatible_shapes(
+                        operator(),
+                        "as many columns on the left as rows on the right",
+                        l,
+                        r,
+                        span,
+                    ));
+                }
+                let elements = (0..a.rows())
+                    .flat_map(|i| (0..b.cols()).map(move |j| (i, j)))
+                    .map(|(i, j)| {
+                        let pairs = (0..a.cols()).map(|k| (a.get(i, k), b.get(k, j)));
+                        self.sum_of_products(pairs, span)
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Value::Matrix(Matrix::new(a.rows(), b.cols(), elements)))
+            }
+            (Value::Matrix(a), Op::Mul, Value::List(v)) => {
+                if a.cols() != v.len() {
+                    return Err(Self::incompatible_shapes(
+                        operator(),
+                        "as many columns on the left as rows on the right",
+                        l,
+                        r,
+                        span,
+                    ));
+                }
+                a.iter_rows()
+                    .map(|row| self.sum_of_products(row.iter().zip(v), span))
+                    .collect::<Result<_>>()
+                    .map(Value::List)
+            }
+            (Value::Matrix(a), Op::Add | Op::Sub, Value::Matrix(b)) => {
+                if (a.rows(), a.cols()) != (b.rows(), b.cols()) {
+                    return Err(Self::incompatible_shapes(
+                        operator(),
+                        "matrices of the same size",
+                        l,
+                        r,
+                        span,
+                    ));
+                }
+                let elements = a
+                    .elements()
+                    .iter()
+                    .zip(b.elements())
+                    .map(|(x, y)| self.binary(x, op, y, span))
+                    .collect::<Result<_>>()?;
+                Ok(Value::Matrix(Matrix::new(a.rows(), a.cols(), elements)))
+            }
+            (Value::Matrix(a), Op::Add | Op::Sub | Op::Mul | Op::Div, _) if !is_collection(r) => {
+                each(a, &|x| self.binary(x, op, r, span))
+            }
+            (_, Op::Add | Op::Sub | Op::Mul, Value::Matrix(b)) if !is_collection(l) => {
+                each(b, &|x| self.binary(l, op, x, span))
+            }
+            _ => Err(Self::mismatch(l, op, r, span)),
+        }
+    }
+
+    pub(super) fn incompatible_shapes(
+        name: String,
+        requirement: &'static str,
+        l: &Value<N>,
+        r: &Value<N>,
+        span: Span,
+    ) -> Error {
+        let shape = |value: &Value<N>| match value {
+            Value::List(list) => format!("a list of {}", list.len()),
+            _ => Self::describe(value),
+        };
+        Error::IncompatibleShapes {
+            name,
+            requirement,
+            lhs: shape(l),
+            rhs: shape(r),
+            span,
+        }
+    }
+}
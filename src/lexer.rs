@@ -1,75 +1,505 @@
+use std::{fmt, iter::Peekable, str::CharIndices};
+
+use crate::{diagnostic::Diagnostic, span::Span};
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("ran out of characters while lexing")]
-    NoCharactersToEat,
+    NoCharactersToEat(Span),
+
+    #[error("invalid character: '{0}'")]
+    InvalidCharacter(char, Span),
+
+    #[error("malformed number: {0}")]
+    MalformedNumber(String, Span),
+
+    #[error("malformed date: {0}")]
+    MalformedDate(String, Span),
+}
 
-    #[error("Invalid character: '{0}'")]
-    InvalidCharacter(char),
+impl Error {
+    pub fn span(&self) -> Span {
+        match *self {
+            Error::NoCharactersToEat(span)
+            | Error::InvalidCharacter(_, span)
+            | Error::MalformedNumber(_, span)
+            | Error::MalformedDate(_, span) => span,
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        match *self {
+            Error::MalformedNumber(ref reason, span) => Diagnostic::error("malformed number")
+                .with_label(span, reason)
+                .with_help("numbers look like 42, 1_000, 3.14, 6.02e23, 0xff, 0o755 or 0b1010"),
+            Error::MalformedDate(ref reason, span) => Diagnostic::error("malformed date")
+                .with_label(span, reason)
+                .with_help(
+                    "dates look like @2026-10-18, @2026-10-18T14:30 or @2026-10-18T14:30:00+02:00",
+                ),
+            Error::NoCharactersToEat(span) => {
+                Diagnostic::error(self.to_string()).with_label(span, "")
+            }
+            Error::InvalidCharacter(c, span) => {
+                Diagnostic::error(format!("invalid character '{c}'"))
+                    .with_label(span, "not valid in an expression")
+                    .with_help(
+                        "expressions are made of numbers, @dates, names of variables, \
+                         functions and units, operators such as + and ->, `? :`, \
+                         parentheses, brackets and commas",
+                    )
+            }
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum TokenKind {
+    And,
+    Arrow,
     Asterisk,
+    BangEquals,
+    Caret,
+    Colon,
+    Comma,
+    Date,
+    DoubleAsterisk,
+    DoubleDot,
+    DoubleEquals,
+    DoubleSlash,
+    Else,
     Eof,
+    Equals,
+    False,
+    Greater,
+    GreaterEquals,
+    Identifier,
+    If,
+    Imaginary,
+    In,
+    LeftBracket,
     LeftParen,
+    Less,
+    LessEquals,
     Minus,
+    Mod,
+    Not,
     Number,
+    Or,
+    Percent,
     Plus,
+    Question,
+    RightBracket,
     RightParen,
     Slash,
+    Then,
+    To,
+    True,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                TokenKind::And => "'and'",
+                TokenKind::Arrow => "'->'",
+                TokenKind::Asterisk => "'*'",
+                TokenKind::BangEquals => "'!='",
+                TokenKind::Caret => "'^'",
+                TokenKind::Colon => "':'",
+                TokenKind::Comma => "','",
+                TokenKind::Date => "date",
+                TokenKind::DoubleAsterisk => "'**'",
+                TokenKind::DoubleDot => "'..'",
+                TokenKind::DoubleEquals => "'=='",
+                TokenKind::DoubleSlash => "'//'",
+                TokenKind::Else => "'else'",
+                TokenKind::Eof => "end of input",
+                TokenKind::Equals => "'='",
+                TokenKind::False => "'false'",
+                TokenKind::Greater => "'>'",
+                TokenKind::GreaterEquals => "'>='",
+                TokenKind::Identifier => "identifier",
+                TokenKind::If => "'if'",
+                TokenKind::Imaginary => "imaginary number",
+                TokenKind::In => "'in'",
+                TokenKind::LeftBracket => "'['",
+                TokenKind::LeftParen => "'('",
+                TokenKind::Less => "'<'",
+                TokenKind::LessEquals => "'<='",
+                TokenKind::Minus => "'-'",
+                TokenKind::Mod => "'mod'",
+                TokenKind::Not => "'not'",
+                TokenKind::Number => "number",
+                TokenKind::Or => "'or'",
+                TokenKind::Percent => "'%'",
+                TokenKind::Plus => "'+'",
+                TokenKind::Question => "'?'",
+                TokenKind::RightBracket => "']'",
+                TokenKind::RightParen => "')'",
+                TokenKind::Slash => "'/'",
+                TokenKind::Then => "'then'",
+                TokenKind::To => "'to'",
+                TokenKind::True => "'true'",
+            }
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct Token {
+pub struct Token<'a> {
     pub kind: TokenKind,
-    pub spelling: String,
+    pub spelling: &'a str,
+    pub span: Span,
 }
 
-pub struct Lexer<S: AsRef<str>> {
-    s: S,
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            TokenKind::Number | TokenKind::Imaginary | TokenKind::Date | TokenKind::Identifier => {
+                write!(f, "{} '{}'", self.kind, self.spelling)
+            }
+            _ => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// Splits the input into tokens in a single pass over its characters.
+/// Token spellings borrow from the input rather than being copied out.
+#[derive(Clone)]
+pub struct Lexer<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
     curr_char: Option<char>,
-    curr_idx: usize,
-    curr_spelling: String,
+    curr_offset: usize,
+    curr_line: usize,
+    curr_column: usize,
+    tok_start: Span,
+    diagnostics: Vec<Diagnostic>,
 }
 
-impl<S: AsRef<str>> Lexer<S> {
-    pub fn new(s: S) -> Self {
-        let curr_idx = 0;
-        let curr_char = s.as_ref().chars().nth(0);
+impl<'a> Lexer<'a> {
+    pub fn new(s: &'a str) -> Self {
+        let mut chars = s.char_indices().peekable();
+        let curr_char = chars.peek().map(|&(_, c)| c);
 
         Self {
             s,
+            chars,
             curr_char,
-            curr_idx,
-            curr_spelling: String::new(),
+            curr_offset: 0,
+            curr_line: 1,
+            curr_column: 1,
+            tok_start: Span::new(0, 0, 1, 1),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Takes the warnings lexing has produced since they were last taken,
+    /// such as a subtraction that looks like a date.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    fn advance(&mut self) {
+        if let Some((_, c)) = self.chars.next() {
+            if c == '\n' {
+                self.curr_line += 1;
+                self.curr_column = 1;
+            } else {
+                self.curr_column += 1;
+            }
         }
+        (self.curr_offset, self.curr_char) = match self.chars.peek() {
+            Some(&(idx, c)) => (idx, Some(c)),
+            None => (self.s.len(), None),
+        };
+    }
+
+    /// A span covering the current character, or an empty one at the end of
+    /// the input.
+    fn here(&self) -> Span {
+        let len = self.curr_char.map_or(0, char::len_utf8);
+        Span::new(
+            self.curr_offset,
+            self.curr_offset + len,
+            self.curr_line,
+            self.curr_column,
+        )
     }
 
     fn skip_it(&mut self) {
-        self.curr_idx += 1;
-        self.curr_char = self.s.as_ref().chars().nth(self.curr_idx);
+        self.advance();
     }
 
+    /// A span from the start of the token being lexed up to the current
+    /// character, including it if `inclusive` is set.
+    fn token_span(&self, inclusive: bool) -> Span {
+        let end = if inclusive {
+            self.here().end
+        } else {
+            self.curr_offset
+        };
+        Span {
+            end,
+            ..self.tok_start
+        }
+    }
+
+    /// Looks at the character after the current one.
+    fn peek_next(&self) -> Option<char> {
+        self.s[self.curr_offset..].chars().nth(1)
+    }
+
+    /// Consumes the current character as part of the token being lexed.
     fn eat_it(&mut self) -> Result<()> {
-        self.curr_spelling
-            .push(self.curr_char.ok_or_else(|| Error::NoCharactersToEat)?);
-        self.curr_idx += 1;
-        self.curr_char = self.s.as_ref().chars().nth(self.curr_idx);
+        if self.curr_char.is_none() {
+            return Err(Error::NoCharactersToEat(self.here()));
+        }
+        self.advance();
+
+        Ok(())
+    }
+
+    /// Whether the current character starts a `..`.
+    fn at_range(&self) -> bool {
+        self.curr_char == Some('.') && self.peek_next() == Some('.')
+    }
+
+    /// Eats a run of digits in `radix`, allowing single underscores between
+    /// them. Returns the number of digits eaten.
+    fn eat_digits(&mut self, radix: u32) -> Result<usize> {
+        let mut digits = 0;
+
+        while let Some(c) = self.curr_char {
+            if c.is_digit(radix) {
+                digits += 1;
+            } else if c == '_' {
+                if !self.peek_next().is_some_and(|c| c.is_digit(radix)) || digits == 0 {
+                    return Err(Error::MalformedNumber(
+                        "'_' must separate two digits".to_owned(),
+                        self.token_span(true),
+                    ));
+                }
+            } else {
+                break;
+            }
+            self.eat_it()?;
+        }
+
+        Ok(digits)
+    }
+
+    /// Number <- Digits ('.' Digits?)? (('e' | 'E') ('+' | '-')? Digits)?
+    ///         | '0' ('x' | 'X') HexDigits
+    ///         | '0' ('o' | 'O') OctDigits
+    ///         | '0' ('b' | 'B') BinDigits
+    ///
+    /// An `Imaginary` is a `Number` directly followed by `i` or `j`. A '.'
+    /// directly followed by another is not a decimal point, so that `1..10`
+    /// is a range.
+    fn lex_number(&mut self) -> Result<()> {
+        let radix = match (self.curr_char, self.peek_next()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('o' | 'O')) => 8,
+            (Some('0'), Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            self.eat_it()?;
+            self.eat_it()?;
+            if self.eat_digits(radix)? == 0 {
+                return Err(Error::MalformedNumber(
+                    "expected digits after the radix prefix".to_owned(),
+                    self.token_span(true),
+                ));
+            }
+            // A letter too, so that `0x1g` is not read as `0x1 g`.
+            if let Some(c) = self
+                .curr_char
+                .filter(|&c| c.is_alphanumeric() || c == '_' || (c == '.' && !self.at_range()))
+            {
+                return Err(Error::MalformedNumber(
+                    format!("'{c}' is not a valid digit in base {radix}"),
+                    self.token_span(true),
+                ));
+            }
+            return Ok(());
+        }
+
+        self.eat_digits(10)?;
+
+        if self.curr_char == Some('.') && !self.at_range() {
+            self.eat_it()?;
+            if self.curr_char == Some('_') {
+                return Err(Error::MalformedNumber(
+                    "'_' must separate two digits".to_owned(),
+                    self.token_span(true),
+                ));
+            }
+            self.eat_digits(10)?;
+        }
+
+        let exponent_follows = match (self.curr_char, self.peek_next()) {
+            (Some('e' | 'E'), Some(c)) if c.is_ascii_digit() => true,
+            (Some('e' | 'E'), Some('+' | '-')) => self.s[self.curr_offset..]
+                .chars()
+                .nth(2)
+                .is_some_and(|c| c.is_ascii_digit()),
+            _ => false,
+        };
+        if exponent_follows {
+            self.eat_it()?;
+            if matches!(self.curr_char, Some('+' | '-')) {
+                self.eat_it()?;
+            }
+            self.eat_digits(10)?;
+        } else if matches!(self.curr_char, Some('e' | 'E')) {
+            // An 'e' that starts a longer name, as in `1 eV`, is a unit
+            // after the number; on its own, as in `2e` or `1e+`, it is an
+            // exponent without digits rather than Euler's number.
+            let starts_name = self
+                .peek_next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_');
+            if !starts_name {
+                self.eat_it()?;
+                return Err(Error::MalformedNumber(
+                    "expected digits after the exponent".to_owned(),
+                    self.token_span(false),
+                ));
+            }
+        }
+
+        if self.curr_char == Some('.') && !self.at_range() {
+            return Err(Error::MalformedNumber(
+                if exponent_follows {
+                    "the exponent must be an integer"
+                } else {
+                    "a number can only have one decimal point"
+                }
+                .to_owned(),
+                self.token_span(true),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the input from the current character on starts with `shape`,
+    /// in which `9` stands for any digit.
+    fn looking_at(&self, shape: &str) -> bool {
+        let rest = &self.s.as_bytes()[self.curr_offset..];
+        rest.len() >= shape.len()
+            && shape.bytes().zip(rest).all(|(s, &c)| match s {
+                b'9' => c.is_ascii_digit(),
+                _ => s == c,
+            })
+    }
+
+    /// Eats the ASCII characters matching `shape`, if they come next.
+    fn eat_shape(&mut self, shape: &str) -> Result<bool> {
+        if !self.looking_at(shape) {
+            return Ok(false);
+        }
+        for _ in 0..shape.len() {
+            self.eat_it()?;
+        }
+        Ok(true)
+    }
+
+    /// Date <- '@' Digit{4} '-' Digit{2} '-' Digit{2} ('T' Time Offset?)?
+    /// Time <- Digit{2} ':' Digit{2} (':' Digit{2})?
+    /// Offset <- 'Z' | ('+' | '-') Digit{2} ':' Digit{2}
+    ///
+    /// The '@' keeps `1000-10-10` a subtraction. Whether the fields are in
+    /// range is left to the parser.
+    fn lex_date(&mut self) -> Result<()> {
+        self.eat_it()?;
+        if !self.eat_shape("9999-99-99")? {
+            return Err(Error::MalformedDate(
+                "expected a date such as 2026-10-18 after '@'".to_owned(),
+                self.token_span(true),
+            ));
+        }
+
+        if self.curr_char == Some('T') {
+            self.eat_it()?;
+            if !self.eat_shape("99:99")? {
+                return Err(Error::MalformedDate(
+                    "expected a time such as 14:30 after 'T'".to_owned(),
+                    self.token_span(true),
+                ));
+            }
+            self.eat_shape(":99")?;
+
+            let utc = self.curr_char == Some('Z')
+                && !self
+                    .peek_next()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_');
+            if utc {
+                self.eat_it()?;
+            } else if !self.eat_shape("+99:99")? {
+                self.eat_shape("-99:99")?;
+            }
+        }
 
+        if let Some(c) = self.curr_char.filter(|c| c.is_ascii_digit() || *c == ':') {
+            return Err(Error::MalformedDate(
+                format!("unexpected '{c}' after the date"),
+                self.token_span(true),
+            ));
+        }
         Ok(())
     }
 
+    /// Warns about a `dddd-dd-dd` without its '@', which is a subtraction
+    /// that was probably meant to be a date.
+    fn warn_bare_date(&mut self) {
+        let rest = &self.s[self.curr_offset..];
+        if !self.looking_at("9999-99-99") || rest[10..].starts_with(|c: char| c.is_ascii_digit()) {
+            return;
+        }
+        let date = &rest[..10];
+        let span = Span {
+            end: self.curr_offset + 10,
+            ..self.here()
+        };
+        self.diagnostics.push(
+            Diagnostic::warning(format!("'{date}' is a subtraction, not a date"))
+                .with_label(span, format!("read as {}", date.replace('-', " - ")))
+                .with_help(format!("write @{date} for the date")),
+        );
+    }
+
     fn lex_it(&mut self) -> Result<TokenKind> {
         let mut tok_kind = TokenKind::Eof;
 
         match self.curr_char {
-            Some(c) if c.is_numeric() => {
-                while self.curr_char.is_some_and(|c| c.is_numeric() || c == '.') {
+            Some('@') => {
+                self.lex_date()?;
+                tok_kind = TokenKind::Date;
+            }
+
+            Some(c) if c.is_ascii_digit() => {
+                self.warn_bare_date();
+                self.lex_number()?;
+                tok_kind = TokenKind::Number;
+
+                // An 'i' or 'j' suffix makes the number imaginary, unless it
+                // starts a name, as the 'in' of `3 in` does.
+                let suffix = matches!(self.curr_char, Some('i' | 'j'))
+                    && !self
+                        .peek_next()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+                if suffix {
                     self.eat_it()?;
+                    tok_kind = TokenKind::Imaginary;
                 }
-                tok_kind = TokenKind::Number;
             }
 
             Some('(') => {
@@ -82,6 +512,22 @@ impl<S: AsRef<str>> Lexer<S> {
                 tok_kind = TokenKind::RightParen;
             }
 
+            Some('[') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::LeftBracket;
+            }
+
+            Some(']') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::RightBracket;
+            }
+
+            Some('.') if self.at_range() => {
+                self.eat_it()?;
+                self.eat_it()?;
+                tok_kind = TokenKind::DoubleDot;
+            }
+
             Some('+') => {
                 self.eat_it()?;
                 tok_kind = TokenKind::Plus;
@@ -89,58 +535,158 @@ impl<S: AsRef<str>> Lexer<S> {
 
             Some('-') => {
                 self.eat_it()?;
-                tok_kind = TokenKind::Minus;
+                tok_kind = if self.curr_char == Some('>') {
+                    self.eat_it()?;
+                    TokenKind::Arrow
+                } else {
+                    TokenKind::Minus
+                };
             }
 
             Some('*') => {
                 self.eat_it()?;
-                tok_kind = TokenKind::Asterisk;
+                tok_kind = if self.curr_char == Some('*') {
+                    self.eat_it()?;
+                    TokenKind::DoubleAsterisk
+                } else {
+                    TokenKind::Asterisk
+                };
+            }
+
+            Some('^') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::Caret;
             }
 
             Some('/') => {
                 self.eat_it()?;
-                tok_kind = TokenKind::Slash;
+                tok_kind = if self.curr_char == Some('/') {
+                    self.eat_it()?;
+                    TokenKind::DoubleSlash
+                } else {
+                    TokenKind::Slash
+                };
             }
 
-            Some(c) => Err(Error::InvalidCharacter(c))?,
+            Some(',') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::Comma;
+            }
+
+            Some('=') => {
+                self.eat_it()?;
+                tok_kind = if self.curr_char == Some('=') {
+                    self.eat_it()?;
+                    TokenKind::DoubleEquals
+                } else {
+                    TokenKind::Equals
+                };
+            }
+
+            Some('!') if self.peek_next() == Some('=') => {
+                self.eat_it()?;
+                self.eat_it()?;
+                tok_kind = TokenKind::BangEquals;
+            }
+
+            Some('<') => {
+                self.eat_it()?;
+                tok_kind = if self.curr_char == Some('=') {
+                    self.eat_it()?;
+                    TokenKind::LessEquals
+                } else {
+                    TokenKind::Less
+                };
+            }
+
+            Some('>') => {
+                self.eat_it()?;
+                tok_kind = if self.curr_char == Some('=') {
+                    self.eat_it()?;
+                    TokenKind::GreaterEquals
+                } else {
+                    TokenKind::Greater
+                };
+            }
+
+            Some('?') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::Question;
+            }
+
+            Some(':') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::Colon;
+            }
+
+            Some('%') => {
+                self.eat_it()?;
+                tok_kind = TokenKind::Percent;
+            }
+
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                while self
+                    .curr_char
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_')
+                {
+                    self.eat_it()?;
+                }
+
+                tok_kind = match &self.s[self.tok_start.start..self.curr_offset] {
+                    "mod" => TokenKind::Mod,
+                    "to" => TokenKind::To,
+                    "in" => TokenKind::In,
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    "if" => TokenKind::If,
+                    "then" => TokenKind::Then,
+                    "else" => TokenKind::Else,
+                    "true" => TokenKind::True,
+                    "false" => TokenKind::False,
+                    _ => TokenKind::Identifier,
+                };
+            }
+
+            Some(c) => {
+                // Step over the offending character so that lexing can resume
+                // after the error has been reported.
+                let span = self.here();
+                self.skip_it();
+                Err(Error::InvalidCharacter(c, span))?
+            }
             None => tok_kind = TokenKind::Eof,
         }
 
         Ok(tok_kind)
     }
 
-    pub fn lex(&mut self) -> Result<Token> {
+    pub fn lex(&mut self) -> Result<Token<'a>> {
         while self.curr_char.is_some_and(|c| c.is_ascii_whitespace()) {
             self.skip_it();
         }
 
-        self.curr_spelling.clear();
+        let (start, line, column) = (self.curr_offset, self.curr_line, self.curr_column);
+        self.tok_start = Span::new(start, start, line, column);
         let tok_kind = self.lex_it()?;
 
         Ok(Token {
             kind: tok_kind,
-            spelling: self.curr_spelling.to_owned(),
+            spelling: &self.s[start..self.curr_offset],
+            span: Span::new(start, self.curr_offset, line, column),
         })
     }
 }
 
-impl<S: AsRef<str>> Iterator for Lexer<S> {
-    type Item = Token;
+/// Yields tokens up to, but not including, the end of the input. Lexing
+/// errors are yielded in place of the offending token.
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.lex() {
-            Ok(tok) => {
-                if tok.kind == TokenKind::Eof {
-                    None
-                } else {
-                    Some(tok)
-                }
-            }
-
-            Err(err) => {
-                eprintln!("{err}");
-                None
-            }
+            Ok(tok) if tok.kind == TokenKind::Eof => None,
+            res => Some(res),
         }
     }
 }
@@ -1,4 +1,11 @@
-mod ast;
+pub mod ast;
+pub mod datetime;
+pub mod diagnostic;
+pub mod environment;
 pub mod evaluator;
 pub mod lexer;
+pub mod number;
 pub mod parser;
+pub mod span;
+pub mod units;
+pub mod value;
@@ -1,5 +1,22 @@
-use awesome_calc::{evaluator::Evaluator, lexer::Lexer, parser::Parser};
-use std::io::{self, Write};
+use awesome_calc::{
+    evaluator::Evaluator,
+    lexer::Lexer,
+    number::{
+        decimal::{Decimal, DecimalContext, Rounding},
+        integer::Division,
+        rational::Rational,
+        Numeric,
+    },
+    parser::Parser,
+    units::currency::Rates,
+    value::{Matrix, Value},
+};
+use std::{
+    env, fmt,
+    io::{self, Write},
+    path::PathBuf,
+    thread,
+};
 
 const SPLASH: &str = r#"
   /_\__      _____  ___  ___  _ __ ___   ___  / __\__ _| | ___
@@ -12,25 +29,450 @@ Welcome! v1.0.0
 
 const PROMPT: &str = ">> ";
 
-fn get_input() -> eyre::Result<String> {
+/// The environment variable naming the exchange rates file to load on start.
+const RATES_VAR: &str = "AWESOME_CALC_RATES";
+
+/// The deepest that `:depth` lets function calls nest, and the stack that
+/// the REPL runs on, which is enough for that many calls.
+const MAX_DEPTH: usize = 10_000;
+const STACK_SIZE: usize = 256 << 20;
+
+const HELP: &str = "\
+Commands:
+  :help              show this message
+  :constants         list the named constants
+  :units             list the units; SI units also take prefixes, as in km
+  :rates [PATH]      show or load the exchange rates, from a JSON or CSV file
+  :physical on|off   include the physical constants (c, h_P, G, k_B, N_A)
+  :strict on|off     report division by zero, NaN and overflow as errors
+  :depth [N]         show or set how deeply function calls may nest
+  :polar on|off      show complex numbers as modulus∠argument
+  :exact on|off      compute with exact fractions; float(x) converts back
+  :decimal on|off    compute with decimals of the set precision
+  :integer on|off    compute with 128-bit integers, reporting overflow
+  :division [MODE]   show or set integer division: exact, truncate or floor
+  :precision [N]     show or set the number of significant decimal digits, up to 1000
+  :rounding [MODE]   show or set decimal rounding: half-even, half-up or toward-zero";
+
+fn get_input() -> eyre::Result<Option<String>> {
     let mut input = String::new();
 
-    io::stdin().read_line(&mut input)?;
-    Ok(input.trim().to_owned())
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(input.trim().to_owned()))
+}
+
+/// The REPL's evaluator, for whichever kind of arithmetic is switched on.
+enum Calculator {
+    Float(Evaluator<f64>),
+    Exact(Evaluator<Rational>),
+    Decimal(Evaluator<Decimal>),
+    Integer(Evaluator<i128>),
+}
+
+/// Evaluates `$body` with `$evaluator` bound to the calculator's evaluator,
+/// whatever its numeric type.
+macro_rules! with_evaluator {
+    ($calculator:expr, $evaluator:ident => $body:expr) => {
+        match $calculator {
+            Calculator::Float($evaluator) => $body,
+            Calculator::Exact($evaluator) => $body,
+            Calculator::Decimal($evaluator) => $body,
+            Calculator::Integer($evaluator) => $body,
+        }
+    };
+}
+
+impl Calculator {
+    fn name(&self) -> &'static str {
+        match self {
+            Calculator::Float(_) => "float",
+            Calculator::Exact(_) => "exact",
+            Calculator::Decimal(_) => "decimal",
+            Calculator::Integer(_) => "integer",
+        }
+    }
+}
+
+/// A value that holds a function, whose body holds numbers of its own type,
+/// and so cannot be converted to another.
+#[derive(Debug)]
+struct HoldsFunction;
+
+/// Whether `value` is a function or has one among its elements.
+fn holds_function<N>(value: &Value<N>) -> bool {
+    match value {
+        Value::Function(_) => true,
+        Value::List(values) => values.iter().any(holds_function),
+        Value::Matrix(matrix) => matrix.elements().iter().any(holds_function),
+        _ => false,
+    }
+}
+
+/// Converts a value to one with numbers of type `M`, by way of `f64` for
+/// real numbers.
+fn convert_value<N: Numeric, M: Numeric>(value: &Value<N>) -> Result<Value<M>, HoldsFunction> {
+    Ok(match *value {
+        Value::Number(ref n) => {
+            let x = n.to_f64();
+            M::from_f64(x).map_or(Value::Float(x), Value::Number)
+        }
+        Value::Float(x) => M::from_f64(x).map_or(Value::Float(x), Value::Number),
+        Value::Complex(z) => Value::Complex(z),
+        Value::Quantity(ref q) => Value::Quantity(q.clone()),
+        Value::Bool(b) => Value::Bool(b),
+        Value::Date(date) => Value::Date(date),
+        Value::DateTime(datetime) => Value::DateTime(datetime),
+        Value::Function(_) => return Err(HoldsFunction),
+        Value::List(ref values) => {
+            Value::List(values.iter().map(convert_value).collect::<Result<_, _>>()?)
+        }
+        Value::Matrix(ref matrix) => Value::Matrix(Matrix::new(
+            matrix.rows(),
+            matrix.cols(),
+            matrix
+                .elements()
+                .iter()
+                .map(convert_value)
+                .collect::<Result<_, _>>()?,
+        )),
+    })
+}
+
+/// A matrix laid out a row to a line, with each column aligned to the right.
+fn aligned<N: fmt::Display>(matrix: &Matrix<N>) -> String {
+    let cells = matrix
+        .elements()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let widths = (0..matrix.cols())
+        .map(|col| {
+            cells
+                .iter()
+                .skip(col)
+                .step_by(matrix.cols())
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    cells
+        .chunks(matrix.cols())
+        .map(|row| {
+            let row = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:>width$}"))
+                .collect::<Vec<_>>();
+            format!("[{}]", row.join("  "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Creates an evaluator in `M` with the settings, constants and functions of
+/// `from`, and its variables converted with [`convert_value`]. Variables
+/// that hold user-defined functions are left out.
+fn convert<N: Numeric, M: Numeric>(from: &Evaluator<N>) -> Evaluator<M> {
+    let mut to = Evaluator::<M>::default();
+    *to.options_mut() = *from.options();
+    *to.constants_mut() = from.constants().clone();
+    *to.functions_mut() = from.functions().clone();
+    *to.units_mut() = from.units().clone();
+    to.environment_mut().extend(
+        from.environment()
+            .iter()
+            .filter_map(|(name, value)| Some((name, convert_value(value).ok()?))),
+    );
+    to
+}
+
+struct Repl {
+    calculator: Calculator,
+    /// The decimal settings, kept while another kind of arithmetic is on.
+    decimal: DecimalContext,
+    /// The integer division, kept likewise.
+    division: Division,
+    /// Whether complex results are shown in polar form.
+    polar: bool,
+    /// The file the exchange rates were loaded from.
+    rates: Option<PathBuf>,
+    /// The lines that evaluated to functions, such as `f(x) = x^2`, or to
+    /// lists holding them, which are evaluated again when the kind of
+    /// arithmetic changes.
+    definitions: Vec<String>,
+}
+
+impl Repl {
+    fn switch_to(&mut self, name: &str) {
+        if self.calculator.name() == name {
+            return;
+        }
+
+        let mut left_out = with_evaluator!(&self.calculator, evaluator => {
+            evaluator
+                .environment()
+                .iter()
+                .filter(|(_, value)| holds_function(value))
+                .map(|(name, _)| name.to_owned())
+                .collect::<Vec<_>>()
+        });
+        left_out.sort();
+
+        self.calculator = with_evaluator!(&self.calculator, evaluator => match name {
+            "exact" => Calculator::Exact(convert(evaluator)),
+            "decimal" => {
+                let mut decimal = convert(evaluator);
+                *decimal.context_mut() = self.decimal;
+                Calculator::Decimal(decimal)
+            }
+            "integer" => {
+                let mut integer = convert(evaluator);
+                *integer.context_mut() = self.division;
+                Calculator::Integer(integer)
+            }
+            _ => Calculator::Float(convert(evaluator)),
+        });
+
+        with_evaluator!(&mut self.calculator, evaluator => {
+            for input in &self.definitions {
+                let mut parser = Parser::new(Lexer::new(input));
+                let result = parser
+                    .parse()
+                    .map_err(|err| err.diagnostic())
+                    .and_then(|ast| evaluator.eval(&ast).map_err(|err| err.diagnostic()));
+                if let Err(diagnostic) = result {
+                    eprint!("{}", diagnostic.render(input));
+                }
+                evaluator.take_diagnostics();
+            }
+            left_out.retain(|name| !evaluator.environment().contains(name));
+        });
+        if !left_out.is_empty() {
+            eprintln!(
+                "{}: not carried over to {name} arithmetic, since functions cannot be \
+                 converted; define them again",
+                left_out.join(", ")
+            );
+        }
+    }
+
+    fn set_decimal(&mut self, decimal: DecimalContext) {
+        self.decimal = decimal;
+        if let Calculator::Decimal(evaluator) = &mut self.calculator {
+            *evaluator.context_mut() = decimal;
+        }
+    }
+
+    fn set_division(&mut self, division: Division) {
+        self.division = division;
+        if let Calculator::Integer(evaluator) = &mut self.calculator {
+            *evaluator.context_mut() = division;
+        }
+    }
+
+    fn load_rates(&mut self, path: PathBuf) {
+        match Rates::load(&path) {
+            Ok(rates) => {
+                with_evaluator!(&mut self.calculator, evaluator => {
+                    evaluator.units_mut().set_rates(&rates)
+                });
+                println!(
+                    "loaded {} currencies, as of {}",
+                    rates.rates.len(),
+                    rates.date
+                );
+                self.rates = Some(path);
+            }
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+
+    /// Runs a REPL command, the part of a line after its leading ':'.
+    fn run_command(&mut self, command: &str) {
+        let mut words = command.split_whitespace();
+
+        match (words.next(), words.next()) {
+            (Some("help"), None) => println!("{HELP}"),
+            (Some("constants"), None) => with_evaluator!(&self.calculator, evaluator => {
+                for (name, constant) in evaluator.constants().iter() {
+                    println!(
+                        "{name:>5} = {:<24} {}",
+                        constant.value, constant.description
+                    );
+                }
+            }),
+            (Some("units"), None) => with_evaluator!(&self.calculator, evaluator => {
+                for (symbol, unit) in evaluator.units().iter() {
+                    println!(
+                        "{symbol:>5} = {:<24} {}",
+                        format!("{} {}", unit.scale, unit.dimension).replace(" 1/", "/"),
+                        unit.description
+                    );
+                }
+            }),
+            (Some("rates"), Some(path)) => self.load_rates(PathBuf::from(path)),
+            (Some("rates"), None) => match &self.rates {
+                Some(path) => println!(
+                    "exchange rates are from {}, as of {}",
+                    path.display(),
+                    with_evaluator!(&self.calculator, evaluator => {
+                        evaluator.units().rates_date().unwrap_or_default().to_owned()
+                    })
+                ),
+                None => {
+                    println!("no exchange rates are loaded; set {RATES_VAR} or use :rates PATH")
+                }
+            },
+            (Some("physical"), Some("on")) => {
+                with_evaluator!(&mut self.calculator, evaluator => {
+                    evaluator.constants_mut().add_physical()
+                })
+            }
+            (Some("physical"), Some("off")) => {
+                with_evaluator!(&mut self.calculator, evaluator => {
+                    evaluator.constants_mut().remove_physical()
+                })
+            }
+            (Some("physical"), None) => println!(
+                "physical constants are {}",
+                if with_evaluator!(&self.calculator, evaluator => {
+                    evaluator.constants().has_physical()
+                }) {
+                    "on"
+                } else {
+                    "off"
+                }
+            ),
+            (Some("strict"), Some(on @ ("on" | "off"))) => {
+                with_evaluator!(&mut self.calculator, evaluator => {
+                    evaluator.options_mut().strict = on == "on"
+                })
+            }
+            (Some("depth"), Some(depth)) => match depth.parse() {
+                Ok(max_depth) if (1..=MAX_DEPTH).contains(&max_depth) => {
+                    with_evaluator!(&mut self.calculator, evaluator => {
+                        evaluator.options_mut().max_depth = max_depth
+                    })
+                }
+                _ => eprintln!("the depth must be between 1 and {MAX_DEPTH} calls"),
+            },
+            (Some("depth"), None) => println!(
+                "function calls may nest {} deep",
+                with_evaluator!(&self.calculator, evaluator => evaluator.options().max_depth)
+            ),
+            (Some("strict"), None) => println!(
+                "strict mode is {}",
+                if with_evaluator!(&self.calculator, evaluator => evaluator.options().strict) {
+                    "on"
+                } else {
+                    "off"
+                }
+            ),
+            (Some("polar"), Some(on @ ("on" | "off"))) => self.polar = on == "on",
+            (Some("polar"), None) => {
+                println!("polar display is {}", if self.polar { "on" } else { "off" })
+            }
+            (Some(name @ ("exact" | "decimal" | "integer")), Some("on")) => self.switch_to(name),
+            (Some("exact" | "decimal" | "integer"), Some("off")) => self.switch_to("float"),
+            (Some("exact" | "decimal" | "integer"), None) => {
+                println!("arithmetic is {}", self.calculator.name())
+            }
+            (Some("precision"), Some(digits)) => match digits.parse() {
+                Ok(precision) => match self.decimal.with_precision(precision) {
+                    Ok(decimal) => self.set_decimal(decimal),
+                    Err(err) => eprintln!("{err}"),
+                },
+                Err(_) => eprintln!("the precision must be a positive number of digits"),
+            },
+            (Some("precision"), None) => println!(
+                "decimals have {} significant digits",
+                self.decimal.precision()
+            ),
+            (Some("rounding"), Some(mode)) => match mode.parse::<Rounding>() {
+                Ok(rounding) => self.set_decimal(self.decimal.with_rounding(rounding)),
+                Err(()) => {
+                    eprintln!(
+                        "unknown rounding mode '{mode}', try half-even, half-up or toward-zero"
+                    )
+                }
+            },
+            (Some("rounding"), None) => println!("decimals round {}", self.decimal.rounding()),
+            (Some("division"), Some(mode)) => match mode.parse() {
+                Ok(division) => self.set_division(division),
+                Err(()) => {
+                    eprintln!("unknown division mode '{mode}', try exact, truncate or floor")
+                }
+            },
+            (Some("division"), None) => println!("integer division is {}", self.division),
+            _ => eprintln!("unknown command ':{command}', try :help"),
+        }
+    }
+
+    fn run_line(&mut self, input: &str) {
+        with_evaluator!(&mut self.calculator, evaluator => {
+            let mut parser = Parser::new(Lexer::new(input));
+            match parser.parse() {
+                Ok(ast) => match evaluator.eval(&ast) {
+                    Ok(Value::Complex(z)) if self.polar => println!("{}", z.polar()),
+                    Ok(Value::Matrix(matrix)) => println!("{}", aligned(&matrix)),
+                    Ok(value) if holds_function(&value) => {
+                        self.definitions.push(input.to_owned());
+                        println!("{value}");
+                    }
+                    Ok(value) => println!("{value}"),
+                    Err(err) => eprint!("{}", err.diagnostic().render(input)),
+                },
+                Err(err) => eprint!("{}", err.diagnostic().render(input)),
+            }
+            for diagnostic in parser.take_diagnostics().into_iter().chain(evaluator.take_diagnostics()) {
+                eprint!("{}", diagnostic.render(input));
+            }
+        })
+    }
 }
 
 fn main() -> eyre::Result<()> {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)?
+        .join()
+        .expect("the REPL does not panic")
+}
+
+fn run() -> eyre::Result<()> {
     println!("{SPLASH}");
 
+    let mut repl = Repl {
+        calculator: Calculator::Float(Evaluator::new()),
+        decimal: DecimalContext::default(),
+        division: Division::default(),
+        polar: false,
+        rates: None,
+        definitions: Vec::new(),
+    };
+    if let Some(path) = env::var_os(RATES_VAR) {
+        repl.load_rates(PathBuf::from(path));
+    }
+
     loop {
         print!("{PROMPT}");
         io::stdout().lock().flush()?;
 
-        let input = get_input()?;
-        let mut parser = Parser::new(Lexer::new(input));
-        match parser.parse() {
-            Ok(ast) => println!("{}", Evaluator::eval(ast)),
-            Err(err) => eprintln!("{err}"),
+        let Some(input) = get_input()? else {
+            println!();
+            return Ok(());
+        };
+        if input.is_empty() {
+            continue;
+        }
+        if let Some(command) = input.strip_prefix(':') {
+            repl.run_command(command);
+            continue;
         }
+
+        repl.run_line(&input);
     }
 }
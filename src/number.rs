@@ -0,0 +1,231 @@
+pub mod bigint;
+pub mod complex;
+pub mod decimal;
+mod float;
+pub mod integer;
+pub mod rational;
+
+use std::fmt;
+
+use self::bigint::BigInt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    #[error("division by zero")]
+    DivisionByZero,
+
+    #[error("overflow")]
+    Overflow,
+
+    #[error("inexact result")]
+    Inexact,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A type of number that expressions can be parsed into and evaluated in.
+///
+/// The operations are fallible: integers overflow, and exact types have no
+/// result for operations such as `2^0.5`. An operation without a result in
+/// the type returns [`Error::Inexact`], and the evaluator then redoes it in
+/// `f64` if [`Numeric::FLOAT_FALLBACK`] allows. The evaluator also calls
+/// the built-in functions that the type does not provide, and looks up the
+/// constants, in `f64`, and converts their results with
+/// [`Numeric::from_f64`].
+pub trait Numeric: Clone + fmt::Debug + fmt::Display + PartialOrd {
+    /// Settings that arithmetic depends on, such as a decimal precision.
+    type Context: Clone + fmt::Debug + Default + PartialEq;
+
+    /// Whether a result that the type cannot hold, such as `sqrt(2)` for an
+    /// exact type, is kept as an `f64` rather than reported as an error.
+    const FLOAT_FALLBACK: bool;
+
+    fn from_literal(literal: &Literal) -> Result<Self>;
+
+    /// Parses the spelling of a number token.
+    fn from_spelling(spelling: &str) -> Result<Self> {
+        Self::from_literal(&Literal::parse(spelling)?)
+    }
+
+    /// Converts the result of a computation done in `f64`, or returns `None`
+    /// if the type cannot hold it, or should not pretend to: exact types
+    /// leave floats as they are.
+    fn from_f64(x: f64) -> Option<Self>;
+
+    fn to_f64(&self) -> f64;
+
+    fn is_zero(&self) -> bool;
+
+    /// Whether the number is neither infinite nor NaN, as only floats can be.
+    fn is_finite(&self) -> bool {
+        true
+    }
+
+    fn is_nan(&self) -> bool {
+        false
+    }
+
+    /// The relative error that rounding a result can introduce, which is
+    /// zero for types whose arithmetic is exact.
+    fn epsilon(_ctx: &Self::Context) -> f64 {
+        0.0
+    }
+
+    fn neg(&self, ctx: &Self::Context) -> Result<Self>;
+
+    fn add(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    fn sub(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    fn mul(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    fn div(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    /// The remainder of truncating division, with the sign of `self`.
+    fn rem(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    /// The remainder of flooring division, with the sign of `other`.
+    fn modulo(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    /// The quotient of `self / other` rounded towards negative infinity.
+    fn floor_div(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    fn pow(&self, other: &Self, ctx: &Self::Context) -> Result<Self>;
+
+    fn abs(&self, ctx: &Self::Context) -> Result<Self> {
+        if *self < Self::from_spelling("0")? {
+            self.neg(ctx)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// `-1`, `0` or `1` as the number is negative, zero or positive.
+    fn signum(&self, ctx: &Self::Context) -> Result<Self> {
+        let zero = Self::from_spelling("0")?;
+        let one = Self::from_spelling("1")?;
+        match self.partial_cmp(&zero) {
+            Some(std::cmp::Ordering::Less) => one.neg(ctx),
+            Some(std::cmp::Ordering::Greater) => Ok(one),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// The largest whole number that is at most `self`.
+    fn floor(&self, ctx: &Self::Context) -> Result<Self> {
+        self.floor_div(&Self::from_spelling("1")?, ctx)
+    }
+
+    /// The smallest whole number that is at least `self`.
+    fn ceil(&self, ctx: &Self::Context) -> Result<Self> {
+        self.neg(ctx)?.floor(ctx)?.neg(ctx)
+    }
+
+    /// The nearest whole number, rounding halves away from zero.
+    fn round(&self, ctx: &Self::Context) -> Result<Self> {
+        let floor = self.floor(ctx)?;
+        if floor == *self {
+            return Ok(floor);
+        }
+        let rounded = self
+            .abs(ctx)?
+            .add(&Self::from_spelling("0.5")?, ctx)?
+            .floor(ctx)?;
+        if *self < Self::from_spelling("0")? {
+            rounded.neg(ctx)
+        } else {
+            Ok(rounded)
+        }
+    }
+
+    /// The non-negative square root, which exact types only have for some
+    /// numbers.
+    fn sqrt(&self, _ctx: &Self::Context) -> Result<Self> {
+        Err(Error::Inexact)
+    }
+}
+
+/// A numeric literal as it was written, kept exact so that each numeric type
+/// can convert it without going through `f64` first.
+///
+/// The literal's value is `digits` read in `radix`, times ten to the power
+/// of `exponent`. Only decimal literals have a non-zero exponent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Literal {
+    pub radix: u32,
+    pub digits: String,
+    pub exponent: i64,
+}
+
+impl Literal {
+    /// Parses the spelling of a number token, which the lexer has already
+    /// checked to be well-formed.
+    pub fn parse(spelling: &str) -> Result<Self> {
+        let spelling = spelling.replace('_', "");
+        let radix = match spelling.get(..2) {
+            Some("0x" | "0X") => 16,
+            Some("0o" | "0O") => 8,
+            Some("0b" | "0B") => 2,
+            _ => 10,
+        };
+
+        if radix != 10 {
+            return Ok(Self {
+                radix,
+                digits: spelling[2..].to_owned(),
+                exponent: 0,
+            });
+        }
+
+        let (mantissa, exponent) = match spelling.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (
+                mantissa,
+                exponent.parse::<i64>().map_err(|_| Error::Overflow)?,
+            ),
+            None => (spelling.as_str(), 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        Ok(Self {
+            radix,
+            digits: format!("{integer}{fraction}"),
+            exponent: exponent.saturating_sub(fraction.len() as i64),
+        })
+    }
+
+    /// The nearest `f64` to the literal's value.
+    pub fn to_f64(&self) -> f64 {
+        if self.radix == 10 {
+            format!("{}e{}", self.digits, self.exponent)
+                .parse()
+                .expect("decimal digits and an exponent form a valid float")
+        } else {
+            BigInt::from_str_radix(&self.digits, self.radix)
+                .expect("literal digits are valid in their radix")
+                .to_f64()
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.radix {
+            16 => "0x",
+            8 => "0o",
+            2 => "0b",
+            _ => "",
+        };
+        let point = usize::try_from(-self.exponent)
+            .ok()
+            .filter(|&point| point > 0 && point < self.digits.len());
+
+        match point {
+            Some(point) => {
+                let (integer, fraction) = self.digits.split_at(self.digits.len() - point);
+                write!(f, "{integer}.{fraction}")
+            }
+            None if self.exponent == 0 => write!(f, "{prefix}{}", self.digits),
+            None => write!(f, "{}e{}", self.digits, self.exponent),
+        }
+    }
+}
@@ -0,0 +1,657 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// An arbitrary-precision integer, stored as a sign and a magnitude of
+/// little-endian base 2^32 limbs with no trailing zero limbs. Zero has an
+/// empty magnitude and is never negative.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn one() -> Self {
+        Self::from(1u32)
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    /// Parses a string of digits in `radix`, with an optional leading sign.
+    pub fn from_str_radix(s: &str, radix: u32) -> Option<Self> {
+        let (negative, digits) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+
+        // Consume as many digits at a time as fit in a limb.
+        let chunk = (u32::MAX.ilog(radix)) as usize;
+        let mut magnitude = Vec::new();
+        for chunk_digits in digits.as_bytes().chunks(chunk) {
+            let chunk_str = std::str::from_utf8(chunk_digits).ok()?;
+            let value = u32::from_str_radix(chunk_str, radix).ok()?;
+            let scale = radix.pow(chunk_digits.len() as u32);
+            mul_small_add(&mut magnitude, scale, value);
+        }
+
+        Some(Self::from_parts(negative, magnitude))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_one(&self) -> bool {
+        !self.negative && self.magnitude == [1]
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.magnitude.first().is_none_or(|limb| limb & 1 == 0)
+    }
+
+    pub fn signum(&self) -> i32 {
+        match (self.is_zero(), self.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            negative: false,
+            magnitude: self.magnitude.clone(),
+        }
+    }
+
+    /// The number of bits in the magnitude.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> Self {
+        let mut base = self.clone();
+        let mut result = Self::one();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Multiplies the magnitude by 2^bits.
+    pub fn shl(&self, bits: u64) -> Self {
+        let (limbs, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+        let mut magnitude = vec![0; limbs];
+        magnitude.reserve(self.magnitude.len() + 1);
+
+        let mut carry = 0;
+        for &limb in &self.magnitude {
+            if bits == 0 {
+                magnitude.push(limb);
+            } else {
+                magnitude.push((limb << bits) | carry);
+                carry = limb >> (32 - bits);
+            }
+        }
+        magnitude.push(carry);
+
+        Self::from_parts(self.negative, magnitude)
+    }
+
+    /// Divides the magnitude by 2^bits, truncating.
+    pub fn shr(&self, bits: u64) -> Self {
+        let (limbs, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+        if limbs >= self.magnitude.len() {
+            return Self::zero();
+        }
+
+        let src = &self.magnitude[limbs..];
+        let magnitude = (0..src.len())
+            .map(|i| {
+                let high = src.get(i + 1).copied().unwrap_or(0);
+                if bits == 0 {
+                    src[i]
+                } else {
+                    (src[i] >> bits) | (high << (32 - bits))
+                }
+            })
+            .collect();
+
+        Self::from_parts(self.negative, magnitude)
+    }
+
+    /// Truncating division, returning the quotient and a remainder with the
+    /// sign of the dividend, or `None` when dividing by zero.
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = div_rem_magnitude(&self.magnitude, &other.magnitude);
+
+        Some((
+            Self::from_parts(self.negative != other.negative, q),
+            Self::from_parts(self.negative, r),
+        ))
+    }
+
+    /// Flooring division, returning the quotient and a remainder with the
+    /// sign of the divisor, or `None` when dividing by zero.
+    pub fn div_mod_floor(&self, other: &Self) -> Option<(Self, Self)> {
+        let (q, r) = self.div_rem(other)?;
+
+        if !r.is_zero() && r.negative != other.negative {
+            Some((&q - &Self::one(), &r + other))
+        } else {
+            Some((q, r))
+        }
+    }
+
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let (_, r) = a.div_rem(&b).expect("b is not zero");
+            (a, b) = (b, r);
+        }
+        a
+    }
+
+    /// The nearest `f64`, or an infinity if the magnitude is too large.
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bits();
+        let value = if bits <= 128 {
+            self.magnitude
+                .iter()
+                .rev()
+                .fold(0u128, |acc, &limb| (acc << 32) | limb as u128) as f64
+        } else {
+            // Keep the top 128 bits, folding the rest into a sticky bit so
+            // that rounding to 53 bits is still correct.
+            let shift = bits - 128;
+            let top = self.shr(shift).abs();
+            let sticky = top.shl(shift) != self.abs();
+            let top = top
+                .magnitude
+                .iter()
+                .rev()
+                .fold(0u128, |acc, &limb| (acc << 32) | limb as u128);
+            ldexp((top | sticky as u128) as f64, shift as i64)
+        };
+
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.bits() > 127 {
+            return None;
+        }
+        let magnitude = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0i128, |acc, &limb| (acc << 32) | limb as i128);
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// The exact value of a finite `f64`, as `mantissa * 2^exponent`.
+    pub fn from_f64_parts(f: f64) -> Option<(Self, i64)> {
+        if !f.is_finite() {
+            return None;
+        }
+        let bits = f.to_bits();
+        let negative = bits >> 63 == 1;
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+
+        let (mantissa, exponent) = if exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), exponent - 1075)
+        };
+        let mantissa = Self::from(mantissa);
+
+        Some((if negative { -mantissa } else { mantissa }, exponent))
+    }
+}
+
+/// Computes `x * 2^exp` without overflowing intermediate powers of two.
+pub(crate) fn ldexp(mut x: f64, mut exp: i64) -> f64 {
+    while exp > 1000 {
+        x *= 2f64.powi(1000);
+        exp -= 1000;
+    }
+    while exp < -1000 {
+        x *= 2f64.powi(-1000);
+        exp += 1000;
+    }
+    x * 2f64.powi(exp as i32)
+}
+
+/// `magnitude = magnitude * scale + add`
+fn mul_small_add(magnitude: &mut Vec<u32>, scale: u32, add: u32) {
+    let mut carry = add as u64;
+    for limb in magnitude.iter_mut() {
+        let t = *limb as u64 * scale as u64 + carry;
+        *limb = t as u32;
+        carry = t >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides `magnitude` in place by `divisor`, returning the remainder.
+fn div_small(magnitude: &mut [u32], divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let t = (rem << 32) | *limb as u64;
+        *limb = (t / divisor as u64) as u32;
+        rem = t % divisor as u64;
+    }
+    rem as u32
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let t = limb as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        result.push(t as u32);
+        carry = t >> 32;
+    }
+    result.push(carry as u32);
+
+    result
+}
+
+/// `a - b`, where `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut t = limb as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        borrow = (t < 0) as i64;
+        if t < 0 {
+            t += 1 << 32;
+        }
+        result.push(t as u32);
+    }
+
+    result
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+
+    result
+}
+
+/// Long division of magnitudes (Knuth, TAOCP vol. 2, 4.3.1, algorithm D).
+fn div_rem_magnitude(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(u, v) == Ordering::Less {
+        return (Vec::new(), u.to_vec());
+    }
+    if v.len() == 1 {
+        let mut q = u.to_vec();
+        let r = div_small(&mut q, v[0]);
+        return (q, vec![r]);
+    }
+
+    let n = v.len();
+    let m = u.len() - n;
+    let s = v[n - 1].leading_zeros();
+
+    // Normalise so that the top limb of the divisor has its high bit set.
+    let shift = |x: &[u32], extra: bool| {
+        let mut out = Vec::with_capacity(x.len() + 1);
+        let mut carry = 0;
+        for &limb in x {
+            out.push(if s == 0 { limb } else { (limb << s) | carry });
+            carry = if s == 0 { 0 } else { limb >> (32 - s) };
+        }
+        if extra {
+            out.push(carry);
+        }
+        out
+    };
+    let vn = shift(v, false);
+    let mut un = shift(u, true);
+
+    let b = 1u64 << 32;
+    let mut q = vec![0u32; m + 1];
+
+    for j in (0..=m).rev() {
+        let num = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut qhat = num / vn[n - 1] as u64;
+        let mut rhat = num % vn[n - 1] as u64;
+
+        while qhat >= b || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= b {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        for i in 0..n {
+            let p = qhat * vn[i] as u64;
+            let t = un[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+            un[i + j] = t as u32;
+            borrow = (p >> 32) as i64 - (t >> 32);
+        }
+        let t = un[j + n] as i64 - borrow;
+        un[j + n] = t as u32;
+
+        q[j] = qhat as u32;
+        if t < 0 {
+            // The estimate was one too large; add the divisor back.
+            q[j] = q[j].wrapping_sub(1);
+            let mut carry = 0u64;
+            for i in 0..n {
+                let t = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+    }
+
+    let r = (0..n)
+        .map(|i| {
+            if s == 0 {
+                un[i]
+            } else {
+                (un[i] >> s) | (un[i + 1] << (32 - s))
+            }
+        })
+        .collect();
+
+    (q, r)
+}
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => {$(
+        impl From<$t> for BigInt {
+            fn from(n: $t) -> Self {
+                let mut n = n as u128;
+                let mut magnitude = Vec::new();
+                while n > 0 {
+                    magnitude.push(n as u32);
+                    n >>= 32;
+                }
+                Self::from_parts(false, magnitude)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_from_signed {
+    ($($t:ty),*) => {$(
+        impl From<$t> for BigInt {
+            fn from(n: $t) -> Self {
+                let magnitude = BigInt::from(n.unsigned_abs());
+                if n < 0 {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            }
+        }
+    )*};
+}
+
+impl_from_unsigned!(u32, u64, u128, usize);
+impl_from_signed!(i32, i64, i128);
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        Self::from_parts(!self.negative, self.magnitude)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        -self.clone()
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+macro_rules! forward_by_value {
+    ($($trait:ident :: $method:ident),*) => {$(
+        impl $trait for BigInt {
+            type Output = BigInt;
+
+            fn $method(self, other: BigInt) -> BigInt {
+                (&self).$method(&other)
+            }
+        }
+    )*};
+}
+
+forward_by_value!(Add::add, Sub::sub, Mul::mul);
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_radix(s, 10).ok_or(())
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+
+        // Peel off nine decimal digits at a time.
+        let mut magnitude = self.magnitude.clone();
+        let mut chunks = Vec::new();
+        while !magnitude.is_empty() {
+            chunks.push(div_small(&mut magnitude, 1_000_000_000));
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:09}"));
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+
+        assert_eq!(big("-864197532086419753208641975320"), &a + &b);
+        assert_eq!(big("1111111110111111111011111111100"), &a - &b);
+        assert_eq!(
+            big("-121932631137021795226185032733622923332237463801111263526900"),
+            &a * &b
+        );
+        assert_eq!(
+            Some((big("-8"), big("-9000000000900000000090"))),
+            b.div_rem(&a)
+        );
+        assert_eq!(
+            Some((big("-9"), big("123456780012345678001234567800"))),
+            b.div_mod_floor(&a)
+        );
+        assert_eq!(
+            big("10000000000000000000000000000000000000000"),
+            big("10").pow(40)
+        );
+        assert_eq!(big("6"), big("-48").gcd(&big("18")));
+    }
+
+    #[test]
+    fn test_division() {
+        // Exercises the add back step of algorithm D.
+        let u = BigInt::from_str_radix("7fffffff800000010000000000000000", 16).unwrap();
+        let v = BigInt::from_str_radix("800000008000000200000005", 16).unwrap();
+        let (q, r) = u.div_rem(&v).unwrap();
+
+        assert!(r < v);
+        assert_eq!(u, &(&q * &v) + &r);
+
+        let u = big("340282366920938463463374607431768211455123456789");
+        let v = big("18446744073709551617");
+        let (q, r) = u.div_rem(&v).unwrap();
+        assert_eq!(u, &(&q * &v) + &r);
+        assert!(r < v);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            "-255",
+            BigInt::from_str_radix("-ff", 16).unwrap().to_string()
+        );
+        assert_eq!(
+            Some(-(1i128 << 100)),
+            BigInt::from(-(1i128 << 100)).to_i128()
+        );
+        assert_eq!(1e30, big("1000000000000000000000000000000").to_f64());
+        assert_eq!(2f64.powi(1023), BigInt::one().shl(1023).to_f64());
+        assert_eq!(f64::INFINITY, BigInt::one().shl(1024).to_f64());
+        assert_eq!(
+            1.0 + 2f64.powi(-52),
+            (&BigInt::one().shl(200) + &BigInt::one().shl(148)).to_f64() / 2f64.powi(200)
+        );
+        assert_eq!(big("12345"), big("12345").shl(70).shr(70));
+    }
+}
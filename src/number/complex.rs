@@ -0,0 +1,278 @@
+use std::{
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// A complex number `re + im·i` with `f64` parts.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    /// The imaginary unit.
+    pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// The complex number with modulus `r` and argument `theta`.
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Self::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    pub fn is_nan(&self) -> bool {
+        self.re.is_nan() || self.im.is_nan()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re == 0.0 && self.im == 0.0
+    }
+
+    /// The modulus.
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The argument, in radians in `(-pi, pi]`.
+    pub fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(&self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    /// The principal square root, with a non-negative real part.
+    pub fn sqrt(&self) -> Self {
+        if self.im == 0.0 {
+            return if self.re >= 0.0 {
+                Self::new(self.re.sqrt(), self.im)
+            } else {
+                Self::new(0.0, (-self.re).sqrt().copysign(self.im))
+            };
+        }
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt().copysign(self.im);
+        Self::new(re, im)
+    }
+
+    pub fn exp(&self) -> Self {
+        Self::from_polar(self.re.exp(), self.im)
+    }
+
+    /// The principal natural logarithm.
+    pub fn ln(&self) -> Self {
+        Self::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn sin(&self) -> Self {
+        Self::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(&self) -> Self {
+        Self::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    pub fn tan(&self) -> Self {
+        self.sin() / self.cos()
+    }
+
+    /// The principal value of `self^other`. Integer powers are computed by
+    /// repeated multiplication, so that `i^2` is exactly `-1`.
+    pub fn pow(&self, other: Self) -> Self {
+        if other.im == 0.0 && other.re.fract() == 0.0 && other.re.abs() <= f64::from(i32::MAX) {
+            return self.powi(other.re as i32);
+        }
+        if self.is_zero() {
+            return if other.re > 0.0 {
+                Self::default()
+            } else {
+                Self::new(f64::NAN, f64::NAN)
+            };
+        }
+        (other * self.ln()).exp()
+    }
+
+    fn powi(&self, n: i32) -> Self {
+        let (mut base, mut exp) = (*self, n.unsigned_abs());
+        let mut result = Self::new(1.0, 0.0);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        if n < 0 {
+            Self::new(1.0, 0.0) / result
+        } else {
+            result
+        }
+    }
+
+    /// Displays the number as its modulus and argument, as in `5∠0.927`.
+    pub fn polar(&self) -> Polar {
+        Polar(*self)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        // Scale by the larger part of the divisor, so that squaring it does
+        // not overflow.
+        if other.re.abs() >= other.im.abs() {
+            let ratio = other.im / other.re;
+            let denom = other.re + other.im * ratio;
+            Self::new(
+                (self.re + self.im * ratio) / denom,
+                (self.im - self.re * ratio) / denom,
+            )
+        } else {
+            let ratio = other.re / other.im;
+            let denom = other.re * ratio + other.im;
+            Self::new(
+                (self.re * ratio + self.im) / denom,
+                (self.im * ratio - self.re) / denom,
+            )
+        }
+    }
+}
+
+/// Displays the number in rectangular form, as in `3+4i`, or `4i` if it
+/// has no real part.
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.re == 0.0 {
+            write!(f, "{}i", self.im)
+        } else if self.im.is_sign_negative() {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+/// A complex number displayed in polar form, from [`Complex::polar`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Polar(Complex);
+
+impl fmt::Display for Polar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}∠{}", self.0.abs(), self.0.arg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (Complex::new(3.0, 4.0), Complex::new(1.0, -2.0));
+
+        assert_eq!(Complex::new(4.0, 2.0), a + b);
+        assert_eq!(Complex::new(2.0, 6.0), a - b);
+        assert_eq!(Complex::new(11.0, -2.0), a * b);
+        assert_eq!(Complex::new(-1.0, 2.0), a / b);
+        assert_eq!(Complex::new(-1.0, 0.0), Complex::I.pow(Complex::from(2.0)));
+        assert_eq!(Complex::new(0.0, -1.0), Complex::I.pow(Complex::from(-1.0)));
+        assert_eq!(Complex::new(-7.0, 24.0), a.pow(Complex::from(2.0)));
+        assert_eq!(5.0, a.abs());
+        assert_eq!(Complex::new(3.0, -4.0), a.conj());
+    }
+
+    #[test]
+    fn test_functions() {
+        let close = |expected: Complex, actual: Complex| (expected - actual).abs() < 1e-12;
+
+        assert_eq!(Complex::I, Complex::from(-1.0).sqrt());
+        assert_eq!(Complex::new(2.0, 1.0), Complex::new(3.0, 4.0).sqrt());
+        assert_eq!(Complex::new(1.0, -2.0), Complex::new(-3.0, -4.0).sqrt());
+        assert!(close(
+            Complex::from(-1.0),
+            (Complex::I * Complex::from(std::f64::consts::PI)).exp()
+        ));
+        assert!(close(
+            Complex::new(0.0, std::f64::consts::PI),
+            Complex::from(-1.0).ln()
+        ));
+        assert!(close(
+            Complex::new(0.0, 1.0),
+            Complex::from(-1.0).pow(Complex::from(0.5))
+        ));
+        assert!(close(
+            Complex::new(1.0, 0.0),
+            Complex::new(0.3, 0.4).sin().pow(Complex::from(2.0))
+                + Complex::new(0.3, 0.4).cos().pow(Complex::from(2.0))
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("3+4i", Complex::new(3.0, 4.0).to_string());
+        assert_eq!("3-4.5i", Complex::new(3.0, -4.5).to_string());
+        assert_eq!("-2i", Complex::new(0.0, -2.0).to_string());
+        assert_eq!("5∠0", Complex::from(5.0).polar().to_string());
+        assert_eq!(
+            format!("2∠{}", std::f64::consts::FRAC_PI_2),
+            Complex::new(0.0, 2.0).polar().to_string()
+        );
+    }
+}
@@ -0,0 +1,676 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use super::{bigint::BigInt, Error, Literal, Numeric, Result};
+
+/// The largest exponent, in either direction, that a decimal can have.
+const MAX_EXPONENT: i64 = 1 << 40;
+
+/// How far apart, in digits, the operands of an operation that needs an
+/// exact integer quotient may be. Beyond this the quotient is not computed.
+const MAX_ALIGNMENT: i64 = 10_000;
+
+/// The most steps of Newton's iteration that a square root takes, which
+/// is plenty for any precision: the correct digits double with each step.
+const MAX_ITERATIONS: usize = 100;
+
+/// The most significant digits a [`DecimalContext`] can keep. Division and
+/// square roots take time quadratic in the precision, so that a billion
+/// digits would leave `1/3` computing for hours.
+pub const MAX_PRECISION: u32 = 1000;
+
+/// How a result with more digits than the precision allows is shortened.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest value, and ties to the one with an even last
+    /// digit.
+    #[default]
+    HalfEven,
+    /// Round to the nearest value, and ties away from zero.
+    HalfUp,
+    /// Drop the extra digits.
+    TowardZero,
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Rounding::HalfEven => "half-even",
+                Rounding::HalfUp => "half-up",
+                Rounding::TowardZero => "toward-zero",
+            }
+        )
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "half-even" => Ok(Rounding::HalfEven),
+            "half-up" => Ok(Rounding::HalfUp),
+            "toward-zero" => Ok(Rounding::TowardZero),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A precision that is zero or more than [`MAX_PRECISION`] digits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("the precision must be between 1 and {MAX_PRECISION} digits, not {0}")]
+pub struct PrecisionOutOfRange(pub u32);
+
+/// The number of significant digits decimal arithmetic keeps, and how it
+/// rounds away the rest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecimalContext {
+    precision: u32,
+    rounding: Rounding,
+}
+
+impl DecimalContext {
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    /// The context with `precision` significant digits, which must be
+    /// between 1 and [`MAX_PRECISION`].
+    pub fn with_precision(self, precision: u32) -> std::result::Result<Self, PrecisionOutOfRange> {
+        if !(1..=MAX_PRECISION).contains(&precision) {
+            return Err(PrecisionOutOfRange(precision));
+        }
+        Ok(Self { precision, ..self })
+    }
+
+    pub fn with_rounding(self, rounding: Rounding) -> Self {
+        Self { rounding, ..self }
+    }
+}
+
+impl Default for DecimalContext {
+    fn default() -> Self {
+        Self {
+            precision: 50,
+            rounding: Rounding::default(),
+        }
+    }
+}
+
+/// A decimal floating point number of arbitrary precision, with the value
+/// `coefficient × 10^exponent`. The coefficient has no trailing zeros, so
+/// every value has a single representation.
+///
+/// Literals convert exactly, and operations round their results to the
+/// precision of a [`DecimalContext`]. Results with an exponent out of range
+/// are an [`Error::Overflow`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    coefficient: BigInt,
+    exponent: i64,
+}
+
+fn pow10(n: i64) -> BigInt {
+    BigInt::from(10u32).pow(n as u32)
+}
+
+fn digit_count(n: &BigInt) -> i64 {
+    n.abs().to_string().len() as i64
+}
+
+impl Decimal {
+    pub fn zero() -> Self {
+        Self {
+            coefficient: BigInt::zero(),
+            exponent: 0,
+        }
+    }
+
+    pub fn one() -> Self {
+        Self {
+            coefficient: BigInt::one(),
+            exponent: 0,
+        }
+    }
+
+    /// The exact value `coefficient × 10^exponent`.
+    pub fn new(coefficient: BigInt, exponent: i64) -> Result<Self> {
+        let (mut coefficient, mut exponent) = (coefficient, exponent);
+
+        if coefficient.is_zero() {
+            return Ok(Self::zero());
+        }
+        let ten = BigInt::from(10u32);
+        loop {
+            let (q, r) = coefficient.div_rem(&ten).expect("ten is not zero");
+            if !r.is_zero() {
+                break;
+            }
+            coefficient = q;
+            exponent = exponent.checked_add(1).ok_or(Error::Overflow)?;
+        }
+
+        if exponent.abs() > MAX_EXPONENT {
+            return Err(Error::Overflow);
+        }
+        Ok(Self {
+            coefficient,
+            exponent,
+        })
+    }
+
+    /// `coefficient × 10^exponent` rounded to the precision of `ctx`.
+    pub fn rounded(coefficient: BigInt, exponent: i64, ctx: &DecimalContext) -> Result<Self> {
+        let excess = digit_count(&coefficient) - i64::from(ctx.precision);
+        if excess <= 0 {
+            return Self::new(coefficient, exponent);
+        }
+
+        let scale = pow10(excess);
+        let (q, r) = coefficient
+            .div_rem(&scale)
+            .expect("a power of ten is not zero");
+        let twice = r.abs().shl(1);
+        let away = match ctx.rounding {
+            Rounding::HalfEven => twice > scale || (twice == scale && !q.is_even()),
+            Rounding::HalfUp => twice >= scale,
+            Rounding::TowardZero => false,
+        };
+        let q = if away {
+            &q + &BigInt::from(coefficient.signum())
+        } else {
+            q
+        };
+        Self::new(q, exponent.checked_add(excess).ok_or(Error::Overflow)?)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.coefficient.is_negative()
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            coefficient: -&self.coefficient,
+            exponent: self.exponent,
+        }
+    }
+
+    /// The exponent of the most significant digit.
+    fn adjusted_exponent(&self) -> i64 {
+        self.exponent + digit_count(&self.coefficient) - 1
+    }
+
+    /// Both coefficients scaled to the smaller of the two exponents, unless
+    /// they are too far apart for that to be worth doing.
+    fn aligned(&self, other: &Self) -> Result<(BigInt, BigInt, i64)> {
+        let exponent = self.exponent.min(other.exponent);
+        if (self.exponent - exponent).max(other.exponent - exponent) > MAX_ALIGNMENT {
+            return Err(Error::Inexact);
+        }
+
+        Ok((
+            &self.coefficient * &pow10(self.exponent - exponent),
+            &other.coefficient * &pow10(other.exponent - exponent),
+            exponent,
+        ))
+    }
+
+    /// `self` truncated to a whole number, and then moved one away from zero
+    /// if `away` holds for the fraction that truncating drops.
+    fn integral(&self, ctx: &DecimalContext, away: impl Fn(&Self) -> bool) -> Result<Self> {
+        if self.exponent >= 0 {
+            return Self::rounded(self.coefficient.clone(), self.exponent, ctx);
+        }
+        let (q, fraction) = if self.adjusted_exponent() < 0 {
+            (BigInt::zero(), self.clone())
+        } else {
+            let (q, r) = self
+                .coefficient
+                .div_rem(&pow10(-self.exponent))
+                .expect("a power of ten is not zero");
+            (q, Self::new(r, self.exponent)?)
+        };
+        let q = if away(&fraction) {
+            &q + &BigInt::from(self.coefficient.signum())
+        } else {
+            q
+        };
+        Self::rounded(q, 0, ctx)
+    }
+
+    /// The decimal if it is an integer that fits in an `i32`.
+    fn to_i32(&self) -> Option<i32> {
+        if !(0..10).contains(&self.exponent) {
+            return None;
+        }
+        let n = (&self.coefficient * &pow10(self.exponent)).to_i128()?;
+        i32::try_from(n).ok()
+    }
+}
+
+impl Numeric for Decimal {
+    type Context = DecimalContext;
+
+    const FLOAT_FALLBACK: bool = true;
+
+    fn from_literal(literal: &Literal) -> Result<Self> {
+        let digits = BigInt::from_str_radix(&literal.digits, literal.radix)
+            .expect("literal digits are valid in their radix");
+        Self::new(digits, literal.exponent)
+    }
+
+    fn from_f64(_: f64) -> Option<Self> {
+        None
+    }
+
+    /// The nearest `f64`.
+    fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.coefficient, self.exponent)
+            .parse()
+            .expect("an integer and an exponent form a valid float")
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    fn epsilon(ctx: &DecimalContext) -> f64 {
+        10f64.powi(1 - ctx.precision as i32)
+    }
+
+    fn neg(&self, ctx: &DecimalContext) -> Result<Self> {
+        Self::rounded(-&self.coefficient, self.exponent, ctx)
+    }
+
+    fn add(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        if other.is_zero() {
+            return Self::rounded(self.coefficient.clone(), self.exponent, ctx);
+        }
+        if self.is_zero() {
+            return Self::rounded(other.coefficient.clone(), other.exponent, ctx);
+        }
+
+        let (hi, lo) = if self.exponent >= other.exponent {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        // Digits of `lo` more than a place below the last digit the sum can
+        // keep only decide which way it rounds, so stand in a single digit
+        // of the same sign for them rather than aligning to all of them.
+        let floor = hi.adjusted_exponent() - i64::from(ctx.precision) - 1;
+        let sticky;
+        let lo = if lo.adjusted_exponent() < floor {
+            sticky = Self {
+                coefficient: BigInt::from(lo.coefficient.signum()),
+                exponent: floor - 1,
+            };
+            &sticky
+        } else {
+            lo
+        };
+
+        let (a, b, exponent) = hi.aligned(lo)?;
+        Self::rounded(&a + &b, exponent, ctx)
+    }
+
+    fn sub(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        self.add(&Decimal::neg(other), ctx)
+    }
+
+    fn mul(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        Self::rounded(
+            &self.coefficient * &other.coefficient,
+            self.exponent
+                .checked_add(other.exponent)
+                .ok_or(Error::Overflow)?,
+            ctx,
+        )
+    }
+
+    fn div(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        if other.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+
+        // Scale the dividend so that the quotient has a digit to spare, and
+        // mark an inexact quotient with a trailing 1 so that it never looks
+        // like a tie.
+        let shift = (i64::from(ctx.precision) + 2 + digit_count(&other.coefficient)
+            - digit_count(&self.coefficient))
+        .max(0);
+        let (q, r) = (&self.coefficient * &pow10(shift))
+            .div_rem(&other.coefficient)
+            .expect("the divisor is not zero");
+        let exponent = self
+            .exponent
+            .checked_sub(other.exponent)
+            .and_then(|exponent| exponent.checked_sub(shift))
+            .ok_or(Error::Overflow)?;
+
+        if r.is_zero() {
+            Self::rounded(q, exponent, ctx)
+        } else {
+            let sign = if self.is_negative() == other.is_negative() {
+                1
+            } else {
+                -1
+            };
+            Self::rounded(
+                &(&q * &BigInt::from(10u32)) + &BigInt::from(sign),
+                exponent - 1,
+                ctx,
+            )
+        }
+    }
+
+    fn rem(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        let (a, b, exponent) = self.aligned(other)?;
+        let (_, r) = a.div_rem(&b).ok_or(Error::DivisionByZero)?;
+        Self::rounded(r, exponent, ctx)
+    }
+
+    fn modulo(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        let (a, b, exponent) = self.aligned(other)?;
+        let (_, r) = a.div_mod_floor(&b).ok_or(Error::DivisionByZero)?;
+        Self::rounded(r, exponent, ctx)
+    }
+
+    fn floor_div(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        let (a, b, _) = self.aligned(other)?;
+        let (q, _) = a.div_mod_floor(&b).ok_or(Error::DivisionByZero)?;
+        Self::rounded(q, 0, ctx)
+    }
+
+    /// Raises `self` to an integer power, carrying a few guard digits
+    /// through the intermediate products.
+    fn pow(&self, other: &Self, ctx: &DecimalContext) -> Result<Self> {
+        let exp = other.to_i32().ok_or(Error::Inexact)?;
+        let work = DecimalContext {
+            precision: ctx.precision + 10,
+            ..*ctx
+        };
+
+        let (mut base, mut n, mut result) = (self.clone(), exp.unsigned_abs(), Self::one());
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.mul(&base, &work)?;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base.mul(&base, &work)?;
+            }
+        }
+        if exp < 0 {
+            result = Self::one().div(&result, &work)?;
+        }
+
+        Self::rounded(result.coefficient, result.exponent, ctx)
+    }
+
+    fn abs(&self, ctx: &DecimalContext) -> Result<Self> {
+        Self::rounded(self.coefficient.abs(), self.exponent, ctx)
+    }
+
+    fn floor(&self, ctx: &DecimalContext) -> Result<Self> {
+        self.integral(ctx, Self::is_negative)
+    }
+
+    fn ceil(&self, ctx: &DecimalContext) -> Result<Self> {
+        self.integral(ctx, |fraction| *fraction > Self::zero())
+    }
+
+    fn round(&self, ctx: &DecimalContext) -> Result<Self> {
+        let half = Self {
+            coefficient: BigInt::from(5u32),
+            exponent: -1,
+        };
+        self.integral(ctx, |fraction| {
+            let magnitude = Self {
+                coefficient: fraction.coefficient.abs(),
+                exponent: fraction.exponent,
+            };
+            magnitude >= half
+        })
+    }
+
+    /// The square root by Newton's iteration, with the same guard digits as
+    /// `pow`. Negative numbers have no decimal square root.
+    fn sqrt(&self, ctx: &DecimalContext) -> Result<Self> {
+        if self.is_negative() {
+            return Err(Error::Inexact);
+        }
+        if self.is_zero() {
+            return Ok(Self::zero());
+        }
+        let work = DecimalContext {
+            precision: ctx.precision + 10,
+            ..*ctx
+        };
+        let half = Self {
+            coefficient: BigInt::from(5u32),
+            exponent: -1,
+        };
+
+        // Within a factor of ten of the root, from where each step about
+        // doubles the correct digits.
+        let mut root = Self::new(BigInt::one(), self.adjusted_exponent() / 2)?;
+        for _ in 0..MAX_ITERATIONS {
+            let next = root
+                .add(&self.div(&root, &work)?, &work)?
+                .mul(&half, &work)?;
+            if next == root {
+                break;
+            }
+            root = next;
+        }
+
+        Self::rounded(root.coefficient, root.exponent, ctx)
+    }
+}
+
+/// Orders by value, comparing the positions of the most significant digits
+/// first so that far apart exponents are not aligned.
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |d: &Self| match (d.coefficient.is_zero(), d.is_negative()) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if sign(self) != 0 => {}
+            ordering => return ordering,
+        }
+
+        let magnitude = match self.adjusted_exponent().cmp(&other.adjusted_exponent()) {
+            Ordering::Equal => {
+                // The exponents now differ by no more than the number of
+                // digits of either coefficient.
+                let exponent = self.exponent.min(other.exponent);
+                let a = &self.coefficient.abs() * &pow10(self.exponent - exponent);
+                let b = &other.coefficient.abs() * &pow10(other.exponent - exponent);
+                a.cmp(&b)
+            }
+            ordering => ordering,
+        };
+        if self.is_negative() {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Writes the decimal in positional notation, unless its most
+    /// significant digit is 10^21 or larger, or smaller than 10^-7.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_negative() { "-" } else { "" };
+        let digits = self.coefficient.abs().to_string();
+        let adjusted = self.adjusted_exponent();
+
+        if !(-7..21).contains(&adjusted) {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            write!(f, "{sign}{first}{point}{rest}e{adjusted}")
+        } else if self.exponent >= 0 {
+            let zeros = "0".repeat(self.exponent as usize);
+            write!(f, "{sign}{digits}{zeros}")
+        } else if adjusted >= 0 {
+            let (integer, fraction) = digits.split_at(adjusted as usize + 1);
+            write!(f, "{sign}{integer}.{fraction}")
+        } else {
+            let zeros = "0".repeat((-adjusted - 1) as usize);
+            write!(f, "{sign}0.{zeros}{digits}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_spelling(s).unwrap()
+    }
+
+    fn ctx(precision: u32, rounding: Rounding) -> DecimalContext {
+        DecimalContext {
+            precision,
+            rounding,
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let ctx50 = DecimalContext::default();
+
+        assert_eq!(dec("0.3"), dec("0.1").add(&dec("0.2"), &ctx50).unwrap());
+        assert_eq!(dec("-0.1"), dec("0.1").sub(&dec("0.2"), &ctx50).unwrap());
+        assert_eq!(dec("1.21"), dec("1.1").mul(&dec("1.1"), &ctx50).unwrap());
+        assert_eq!(
+            "0.33333333333333333333333333333333333333333333333333",
+            dec("1").div(&dec("3"), &ctx50).unwrap().to_string()
+        );
+        assert_eq!(
+            Err(Error::DivisionByZero),
+            dec("1").div(&Decimal::zero(), &ctx50)
+        );
+        assert_eq!(dec("3"), dec("7.5").floor_div(&dec("2.5"), &ctx50).unwrap());
+        assert_eq!(dec("-0.5"), dec("-7.5").rem(&dec("1"), &ctx50).unwrap());
+        assert_eq!(dec("0.5"), dec("-7.5").modulo(&dec("1"), &ctx50).unwrap());
+        assert_eq!(
+            dec("0.0009765625"),
+            dec("2").pow(&dec("-10"), &ctx50).unwrap()
+        );
+        assert_eq!(
+            dec("1e100"),
+            dec("1e100").add(&dec("1e-100"), &ctx50).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        let two_thirds = |rounding| dec("2").div(&dec("3"), &ctx(5, rounding)).unwrap();
+        assert_eq!(dec("0.66667"), two_thirds(Rounding::HalfEven));
+        assert_eq!(dec("0.66667"), two_thirds(Rounding::HalfUp));
+        assert_eq!(dec("0.66666"), two_thirds(Rounding::TowardZero));
+
+        let tie = |n: &str, rounding| dec(n).mul(&Decimal::one(), &ctx(2, rounding)).unwrap();
+        assert_eq!(dec("0.12"), tie("0.125", Rounding::HalfEven));
+        assert_eq!(dec("0.14"), tie("0.135", Rounding::HalfEven));
+        assert_eq!(dec("0.13"), tie("0.125", Rounding::HalfUp));
+        assert_eq!(dec("-0.13"), tie("-0.125", Rounding::HalfUp));
+        assert_eq!(dec("0.12"), tie("0.129", Rounding::TowardZero));
+
+        let almost = dec("1").sub(&dec("1e-60"), &ctx(10, Rounding::TowardZero));
+        assert_eq!(dec("0.9999999999"), almost.unwrap());
+    }
+
+    #[test]
+    fn test_functions() {
+        let ctx50 = DecimalContext::default();
+
+        assert_eq!(dec("2.5"), dec("-2.5").abs(&ctx50).unwrap());
+        assert_eq!(dec("-3"), dec("-2.5").floor(&ctx50).unwrap());
+        assert_eq!(dec("-2"), dec("-2.5").ceil(&ctx50).unwrap());
+        assert_eq!(dec("-3"), dec("-2.5").round(&ctx50).unwrap());
+        assert_eq!(dec("1"), dec("0.5").round(&ctx50).unwrap());
+        assert_eq!(dec("0"), dec("0.49").round(&ctx50).unwrap());
+        assert_eq!(dec("1"), dec("1e-100").ceil(&ctx50).unwrap());
+        assert_eq!(dec("-1"), dec("-1e-100").floor(&ctx50).unwrap());
+        assert_eq!(
+            dec("12345678901234567890"),
+            dec("12345678901234567890.5").floor(&ctx50).unwrap()
+        );
+
+        assert_eq!(dec("12"), dec("144").sqrt(&ctx50).unwrap());
+        assert_eq!(dec("0.03"), dec("0.0009").sqrt(&ctx50).unwrap());
+        assert_eq!(
+            "1.4142135623730950488016887242096980785696718753769",
+            dec("2").sqrt(&ctx50).unwrap().to_string()
+        );
+        assert_eq!(
+            dec("1.41"),
+            dec("2").sqrt(&ctx(3, Rounding::HalfEven)).unwrap()
+        );
+        assert_eq!(Err(Error::Inexact), dec("-2").sqrt(&ctx50));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("1.5", dec("1.50").to_string());
+        assert_eq!("-0.0025", dec("-25e-4").to_string());
+        assert_eq!("1200", dec("12e2").to_string());
+        assert_eq!("1.5e21", dec("15e20").to_string());
+        assert_eq!("1e-8", dec("0.00000001").to_string());
+        assert_eq!("0", Decimal::zero().to_string());
+    }
+
+    #[test]
+    fn test_precision() {
+        for precision in [0, MAX_PRECISION + 1, 1_000_000_000] {
+            assert_eq!(
+                Err(PrecisionOutOfRange(precision)),
+                DecimalContext::default().with_precision(precision)
+            );
+        }
+        assert_eq!(
+            "the precision must be between 1 and 1000 digits, not 0",
+            PrecisionOutOfRange(0).to_string()
+        );
+
+        let widest = DecimalContext::default()
+            .with_precision(MAX_PRECISION)
+            .unwrap();
+        let third = Decimal::one().div(&dec("3"), &widest).unwrap();
+        assert_eq!(i64::from(MAX_PRECISION), digit_count(&third.coefficient));
+        let root = dec("2").sqrt(&widest).unwrap();
+        assert_eq!(i64::from(MAX_PRECISION), digit_count(&root.coefficient));
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(dec("0.1") < dec("0.25"));
+        assert!(dec("-2") < dec("-1.5"));
+        assert!(dec("-1e100") < Decimal::zero());
+        assert!(dec("1e-100") > Decimal::zero());
+        assert!(dec("99") < dec("1e2"));
+        assert!(dec("123.45") > dec("123.4"));
+        assert_eq!(Ordering::Equal, dec("1.50").cmp(&dec("1.5")));
+    }
+}
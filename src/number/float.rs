@@ -0,0 +1,106 @@
+use super::{Literal, Numeric, Result};
+
+/// Implements [`Numeric`] for a float type with IEEE 754 semantics, so that
+/// its operations never fail.
+macro_rules! impl_numeric_for_float {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                type Context = ();
+
+                const FLOAT_FALLBACK: bool = true;
+
+                fn from_literal(literal: &Literal) -> Result<Self> {
+                    Ok(literal.to_f64() as $t)
+                }
+
+                fn from_f64(x: f64) -> Option<Self> {
+                    Some(x as $t)
+                }
+
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == 0.0
+                }
+
+                fn is_finite(&self) -> bool {
+                    <$t>::is_finite(*self)
+                }
+
+                fn is_nan(&self) -> bool {
+                    <$t>::is_nan(*self)
+                }
+
+                fn epsilon(_: &()) -> f64 {
+                    <$t>::EPSILON as f64
+                }
+
+                fn neg(&self, _: &()) -> Result<Self> {
+                    Ok(-self)
+                }
+
+                fn add(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self + other)
+                }
+
+                fn sub(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self - other)
+                }
+
+                fn mul(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self * other)
+                }
+
+                fn div(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self / other)
+                }
+
+                fn rem(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self % other)
+                }
+
+                fn modulo(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self - other * (self / other).floor())
+                }
+
+                fn floor_div(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok((self / other).floor())
+                }
+
+                fn pow(&self, other: &Self, _: &()) -> Result<Self> {
+                    Ok(self.powf(*other))
+                }
+
+                fn abs(&self, _: &()) -> Result<Self> {
+                    Ok(<$t>::abs(*self))
+                }
+
+                fn signum(&self, _: &()) -> Result<Self> {
+                    // `0.0` and NaN are their own sign.
+                    Ok(if *self == 0.0 || <$t>::is_nan(*self) {
+                        *self
+                    } else {
+                        <$t>::signum(*self)
+                    })
+                }
+
+                fn floor(&self, _: &()) -> Result<Self> {
+                    Ok(<$t>::floor(*self))
+                }
+
+                fn ceil(&self, _: &()) -> Result<Self> {
+                    Ok(<$t>::ceil(*self))
+                }
+
+                fn round(&self, _: &()) -> Result<Self> {
+                    Ok(<$t>::round(*self))
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_for_float!(f32, f64);
@@ -0,0 +1,219 @@
+use std::{fmt, str::FromStr};
+
+use super::{rational::Rational, Error, Literal, Numeric, Result};
+
+/// What integer division `/` does with a quotient that is not an integer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Division {
+    /// Report an [`Error::Inexact`].
+    #[default]
+    Exact,
+    /// Round the quotient towards zero.
+    Truncate,
+    /// Round the quotient towards negative infinity, as `//` does.
+    Floor,
+}
+
+impl fmt::Display for Division {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Division::Exact => "exact",
+                Division::Truncate => "truncate",
+                Division::Floor => "floor",
+            }
+        )
+    }
+}
+
+impl FromStr for Division {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Division::Exact),
+            "truncate" => Ok(Division::Truncate),
+            "floor" => Ok(Division::Floor),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The largest magnitude up to which every whole `f64` is exact, 2^53.
+const MAX_EXACT_F64: f64 = 9_007_199_254_740_992.0;
+
+/// Implements [`Numeric`] for a primitive integer type with checked
+/// arithmetic: overflow is an [`Error::Overflow`], and a power without an
+/// integer result is an [`Error::Inexact`]. So is a division, unless the
+/// [`Division`] context rounds the quotient.
+macro_rules! impl_numeric_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Numeric for $t {
+                type Context = Division;
+
+                const FLOAT_FALLBACK: bool = false;
+
+                fn from_literal(literal: &Literal) -> Result<Self> {
+                    let exact = Rational::from_literal(literal).ok_or(Error::Overflow)?;
+                    if !exact.is_integer() {
+                        return Err(Error::Inexact);
+                    }
+                    exact
+                        .numer()
+                        .to_i128()
+                        .and_then(|n| <$t>::try_from(n).ok())
+                        .ok_or(Error::Overflow)
+                }
+
+                /// Only whole numbers up to 2^53 in magnitude: beyond that an
+                /// `f64` is not exact, and the result would pass for a
+                /// precise integer that it is not.
+                fn from_f64(x: f64) -> Option<Self> {
+                    (x.fract() == 0.0 && x.abs() <= MAX_EXACT_F64).then_some(x as $t)
+                }
+
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == 0
+                }
+
+                fn neg(&self, _: &Division) -> Result<Self> {
+                    self.checked_neg().ok_or(Error::Overflow)
+                }
+
+                fn add(&self, other: &Self, _: &Division) -> Result<Self> {
+                    self.checked_add(*other).ok_or(Error::Overflow)
+                }
+
+                fn sub(&self, other: &Self, _: &Division) -> Result<Self> {
+                    self.checked_sub(*other).ok_or(Error::Overflow)
+                }
+
+                fn mul(&self, other: &Self, _: &Division) -> Result<Self> {
+                    self.checked_mul(*other).ok_or(Error::Overflow)
+                }
+
+                fn div(&self, other: &Self, ctx: &Division) -> Result<Self> {
+                    if *ctx == Division::Floor {
+                        return self.floor_div(other, ctx);
+                    }
+                    if self.rem(other, ctx)? != 0 && *ctx == Division::Exact {
+                        return Err(Error::Inexact);
+                    }
+                    self.checked_div(*other).ok_or(Error::Overflow)
+                }
+
+                fn rem(&self, other: &Self, _: &Division) -> Result<Self> {
+                    if *other == 0 {
+                        return Err(Error::DivisionByZero);
+                    }
+                    Ok(self.wrapping_rem(*other))
+                }
+
+                fn modulo(&self, other: &Self, ctx: &Division) -> Result<Self> {
+                    let rem = self.rem(other, ctx)?;
+                    if rem != 0 && (rem < 0) != (*other < 0) {
+                        Ok(rem + other)
+                    } else {
+                        Ok(rem)
+                    }
+                }
+
+                fn floor_div(&self, other: &Self, ctx: &Division) -> Result<Self> {
+                    let rem = self.rem(other, ctx)?;
+                    let quotient = self.checked_div(*other).ok_or(Error::Overflow)?;
+                    if rem != 0 && (rem < 0) != (*other < 0) {
+                        Ok(quotient - 1)
+                    } else {
+                        Ok(quotient)
+                    }
+                }
+
+                fn pow(&self, other: &Self, _: &Division) -> Result<Self> {
+                    match (*self, *other) {
+                        (0, exp) if exp < 0 => Err(Error::DivisionByZero),
+                        (1, _) => Ok(1),
+                        (-1, exp) => Ok(if exp % 2 == 0 { 1 } else { -1 }),
+                        (_, exp) if exp < 0 => Err(Error::Inexact),
+                        (base, exp) => u32::try_from(exp)
+                            .ok()
+                            .and_then(|exp| base.checked_pow(exp))
+                            .ok_or(Error::Overflow),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_for_integer!(i64, i128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let ctx = &Division::Exact;
+
+        assert_eq!(Ok(1000), i64::from_spelling("1e3"));
+        assert_eq!(Ok(15), i64::from_spelling("1.50e1"));
+        assert_eq!(Err(Error::Inexact), i64::from_spelling("1.5"));
+        assert_eq!(
+            Err(Error::Overflow),
+            i64::from_spelling("0x8000_0000_0000_0000")
+        );
+
+        assert_eq!(Err(Error::Overflow), i64::MAX.add(&1, ctx));
+        assert_eq!(Err(Error::Overflow), i64::MIN.neg(ctx));
+        assert_eq!(Err(Error::Overflow), i64::MIN.div(&-1, ctx));
+        assert_eq!(Ok(0), i64::MIN.rem(&-1, ctx));
+        assert_eq!(Ok(-3), (-21i64).div(&7, ctx));
+        assert_eq!(Err(Error::Inexact), 7i64.div(&2, ctx));
+        assert_eq!(Err(Error::DivisionByZero), 7i64.rem(&0, ctx));
+        assert_eq!(Ok(2), (-7i64).modulo(&3, ctx));
+        assert_eq!(Ok(-3), (-7i64).floor_div(&3, ctx));
+        assert_eq!(Ok(-1), Numeric::pow(&-1i64, &-3, ctx));
+        assert_eq!(Err(Error::Inexact), Numeric::pow(&2i64, &-1, ctx));
+        assert_eq!(Err(Error::Overflow), Numeric::pow(&2i64, &63, ctx));
+    }
+
+    #[test]
+    fn test_division() {
+        let (truncate, floor) = (&Division::Truncate, &Division::Floor);
+
+        assert_eq!(Ok(-3), (-7i128).div(&2, truncate));
+        assert_eq!(Ok(-4), (-7i128).div(&2, floor));
+        assert_eq!(Ok(3), (-7i128).div(&-2, truncate));
+        assert_eq!(Ok(3), (-7i128).div(&-2, floor));
+        assert_eq!(Err(Error::DivisionByZero), 7i128.div(&0, truncate));
+        assert_eq!(Err(Error::DivisionByZero), 7i128.div(&0, floor));
+        assert_eq!(Err(Error::Overflow), i128::MIN.div(&-1, floor));
+        assert_eq!(Ok(Division::Floor), "floor".parse());
+        assert_eq!("truncate", Division::Truncate.to_string());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Some(4), i64::from_f64(4.0));
+        assert_eq!(None, i64::from_f64(0.5));
+        assert_eq!(None, i64::from_f64(9.3e18));
+        assert_eq!(Some(-(1 << 53)), i64::from_f64(-(2f64.powi(53))));
+        assert_eq!(None, i64::from_f64(2f64.powi(53) + 2.0));
+        assert_eq!(None, i128::from_f64(2f64.powi(100)));
+        assert_eq!(
+            Ok(i128::MAX),
+            i128::from_spelling("170_141_183_460_469_231_731_687_303_715_884_105_727")
+        );
+        assert_eq!(
+            Err(Error::Overflow),
+            i128::from_spelling("170_141_183_460_469_231_731_687_303_715_884_105_728")
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use std::{cmp::Ordering, fmt};
+
+use super::{bigint::BigInt, Error, Literal, Numeric, Result};
+
+/// The largest power of ten that [`Rational::from_literal`] expands.
+const MAX_LITERAL_EXPONENT: u64 = 10_000;
+
+/// The size, in bits, beyond which a power is not computed exactly.
+const MAX_POW_BITS: u64 = 1 << 16;
+
+/// An exact fraction of arbitrary-precision integers, kept in lowest terms
+/// with a positive denominator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+
+impl Rational {
+    /// Creates the fraction `numer / denom`, or `None` if `denom` is zero.
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Self> {
+        if denom.is_zero() {
+            return None;
+        }
+
+        let gcd = numer.gcd(&denom);
+        let (mut numer, mut denom) = if gcd.is_one() {
+            (numer, denom)
+        } else {
+            (
+                numer.div_rem(&gcd).expect("gcd is not zero").0,
+                denom.div_rem(&gcd).expect("gcd is not zero").0,
+            )
+        };
+        if denom.is_negative() {
+            (numer, denom) = (-numer, -denom);
+        }
+
+        Some(Self { numer, denom })
+    }
+
+    pub fn zero() -> Self {
+        Self::from(BigInt::zero())
+    }
+
+    pub fn one() -> Self {
+        Self::from(BigInt::one())
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom.is_one()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numer.is_negative()
+    }
+
+    /// The exact value of a literal, or `None` if its exponent is too large
+    /// for the value to be worth writing out in full.
+    pub fn from_literal(literal: &Literal) -> Option<Self> {
+        if literal.exponent.unsigned_abs() > MAX_LITERAL_EXPONENT {
+            return None;
+        }
+
+        let digits = BigInt::from_str_radix(&literal.digits, literal.radix)
+            .expect("literal digits are valid in their radix");
+        let scale = BigInt::from(10u32).pow(literal.exponent.unsigned_abs() as u32);
+
+        if literal.exponent >= 0 {
+            Some(Self::from(&digits * &scale))
+        } else {
+            Self::new(digits, scale)
+        }
+    }
+
+    /// The exact value of a finite `f64`.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        let (mantissa, exponent) = BigInt::from_f64_parts(f)?;
+
+        if exponent >= 0 {
+            Some(Self::from(mantissa.shl(exponent as u64)))
+        } else {
+            Self::new(mantissa, BigInt::one().shl(exponent.unsigned_abs()))
+        }
+    }
+
+    /// The nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        if self.is_integer() {
+            return self.numer.to_f64();
+        }
+
+        // Scale the numerator so that the integer quotient carries well over
+        // 53 significant bits, and fold any remainder into a sticky bit.
+        let shift = 128 + self.denom.bits() as i64 - self.numer.bits() as i64;
+        let numer = if shift > 0 {
+            self.numer.shl(shift as u64)
+        } else {
+            self.numer.shr(shift.unsigned_abs())
+        };
+        let (q, r) = numer.div_rem(&self.denom).expect("denominator is not zero");
+        let q = if r.is_zero() {
+            q
+        } else {
+            &q.shl(1) + &BigInt::from(q.signum())
+        };
+        let extra = if r.is_zero() { 0 } else { 1 };
+
+        super::bigint::ldexp(q.to_f64(), -shift - extra)
+    }
+
+    pub fn recip(&self) -> Option<Self> {
+        Self::new(self.denom.clone(), self.numer.clone())
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Self::new(
+            &(&self.numer * &other.denom) + &(&other.numer * &self.denom),
+            &self.denom * &other.denom,
+        )
+        .expect("denominators are not zero")
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::new(&self.numer * &other.numer, &self.denom * &other.denom)
+            .expect("denominators are not zero")
+    }
+
+    /// `self / other`, or `None` if `other` is zero.
+    pub fn div(&self, other: &Self) -> Option<Self> {
+        Self::new(&self.numer * &other.denom, &self.denom * &other.numer)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            numer: -&self.numer,
+            denom: self.denom.clone(),
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            numer: self.numer.abs(),
+            denom: self.denom.clone(),
+        }
+    }
+
+    /// The largest integer not greater than `self`.
+    pub fn floor(&self) -> Self {
+        let (q, _) = self
+            .numer
+            .div_mod_floor(&self.denom)
+            .expect("denominator is not zero");
+        Self::from(q)
+    }
+
+    /// The quotient of `self / other` rounded towards negative infinity, or
+    /// `None` if `other` is zero.
+    pub fn floor_div(&self, other: &Self) -> Option<Self> {
+        Some(self.div(other)?.floor())
+    }
+
+    /// The remainder of truncating division, with the sign of `self`.
+    pub fn rem(&self, other: &Self) -> Option<Self> {
+        let q = self.div(other)?;
+        let (truncated, _) = q.numer.div_rem(&q.denom).expect("denominator is not zero");
+        Some(self.sub(&other.mul(&Self::from(truncated))))
+    }
+
+    /// The remainder of flooring division, with the sign of `other`.
+    pub fn modulo(&self, other: &Self) -> Option<Self> {
+        Some(self.sub(&other.mul(&self.floor_div(other)?)))
+    }
+
+    /// Raises `self` to an integer power, or returns `None` for a negative
+    /// power of zero.
+    pub fn pow(&self, exp: i32) -> Option<Self> {
+        let magnitude = Self {
+            numer: self.numer.pow(exp.unsigned_abs()),
+            denom: self.denom.pow(exp.unsigned_abs()),
+        };
+        if exp < 0 {
+            magnitude.recip()
+        } else {
+            Some(magnitude)
+        }
+    }
+}
+
+impl Numeric for Rational {
+    type Context = ();
+
+    const FLOAT_FALLBACK: bool = true;
+
+    fn from_literal(literal: &Literal) -> Result<Self> {
+        Rational::from_literal(literal).ok_or(Error::Overflow)
+    }
+
+    fn from_f64(_: f64) -> Option<Self> {
+        None
+    }
+
+    fn to_f64(&self) -> f64 {
+        Rational::to_f64(self)
+    }
+
+    fn is_zero(&self) -> bool {
+        Rational::is_zero(self)
+    }
+
+    fn neg(&self, _: &()) -> Result<Self> {
+        Ok(Rational::neg(self))
+    }
+
+    fn add(&self, other: &Self, _: &()) -> Result<Self> {
+        Ok(Rational::add(self, other))
+    }
+
+    fn sub(&self, other: &Self, _: &()) -> Result<Self> {
+        Ok(Rational::sub(self, other))
+    }
+
+    fn mul(&self, other: &Self, _: &()) -> Result<Self> {
+        Ok(Rational::mul(self, other))
+    }
+
+    fn div(&self, other: &Self, _: &()) -> Result<Self> {
+        Rational::div(self, other).ok_or(Error::DivisionByZero)
+    }
+
+    fn rem(&self, other: &Self, _: &()) -> Result<Self> {
+        Rational::rem(self, other).ok_or(Error::DivisionByZero)
+    }
+
+    fn modulo(&self, other: &Self, _: &()) -> Result<Self> {
+        Rational::modulo(self, other).ok_or(Error::DivisionByZero)
+    }
+
+    fn floor_div(&self, other: &Self, _: &()) -> Result<Self> {
+        Rational::floor_div(self, other).ok_or(Error::DivisionByZero)
+    }
+
+    /// Raises `self` to an integer power. A result too large to be worth
+    /// computing exactly is an [`Error::Overflow`], rather than an
+    /// [`Error::Inexact`] that would hand it to `f64`.
+    fn pow(&self, other: &Self, _: &()) -> Result<Self> {
+        if !other.is_integer() {
+            return Err(Error::Inexact);
+        }
+        // 0, 1 and -1 only depend on the sign and parity of the exponent.
+        if self.numer.is_zero() || (self.numer.abs().is_one() && self.denom.is_one()) {
+            let exp = match (other.numer.signum(), other.numer.is_even()) {
+                (0, _) => 0,
+                (sign, true) => 2 * sign,
+                (sign, false) => sign,
+            };
+            return Rational::pow(self, exp).ok_or(Error::DivisionByZero);
+        }
+
+        let exp = other
+            .numer
+            .to_i128()
+            .and_then(|exp| i32::try_from(exp).ok())
+            .ok_or(Error::Overflow)?;
+        let bits = (self.numer.bits() + self.denom.bits()) * u64::from(exp.unsigned_abs());
+        if bits > MAX_POW_BITS {
+            return Err(Error::Overflow);
+        }
+        Rational::pow(self, exp).ok_or(Error::DivisionByZero)
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Self {
+        Self {
+            numer: n,
+            denom: BigInt::one(),
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::from(BigInt::from(n))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(numer: i64, denom: i64) -> Rational {
+        Rational::new(BigInt::from(numer), BigInt::from(denom)).unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(ratio(1, 2), ratio(1, 3).add(&ratio(1, 6)));
+        assert_eq!(ratio(-1, 6), ratio(1, 6).sub(&ratio(1, 3)));
+        assert_eq!(Some(ratio(1, 1)), ratio(1, 3).div(&ratio(1, 3)));
+        assert_eq!(None, ratio(1, 3).div(&Rational::zero()));
+        assert_eq!(Some(ratio(-1, 3)), ratio(-7, 3).rem(&ratio(1, 1)));
+        assert_eq!(Some(ratio(2, 3)), ratio(-7, 3).modulo(&ratio(1, 1)));
+        assert_eq!(Some(ratio(-3, 1)), ratio(-7, 3).floor_div(&ratio(1, 1)));
+        assert_eq!(Some(ratio(27, 8)), ratio(2, 3).pow(-3));
+        assert_eq!("-7/3", ratio(14, -6).to_string());
+    }
+
+    #[test]
+    fn test_pow() {
+        let pow = |base: Rational, exp: i64| Numeric::pow(&base, &Rational::from(exp), &());
+
+        assert_eq!(Ok(ratio(1, 1024)), pow(ratio(1, 2), 10));
+        assert_eq!(Err(Error::Overflow), pow(ratio(1, 2), 30_000));
+        assert_eq!(Err(Error::Overflow), pow(ratio(2, 1), 1 << 40));
+        assert_eq!(Ok(ratio(-1, 1)), pow(ratio(-1, 1), (1 << 40) + 1));
+        assert_eq!(Ok(Rational::zero()), pow(Rational::zero(), 1 << 40));
+        assert_eq!(
+            Err(Error::DivisionByZero),
+            pow(Rational::zero(), -(1 << 40))
+        );
+        assert_eq!(
+            Err(Error::Inexact),
+            Numeric::pow(&ratio(2, 1), &ratio(1, 2), &())
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(0.1, ratio(1, 10).to_f64());
+        assert_eq!(1.0 / 3.0, ratio(1, 3).to_f64());
+        assert_eq!(-2.0 / 3.0, ratio(-2, 3).to_f64());
+        assert_eq!(Some(ratio(3, 4)), Rational::from_f64(0.75));
+        assert_eq!(0.1, Rational::from_f64(0.1).unwrap().to_f64());
+    }
+}
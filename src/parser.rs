@@ -1,147 +1,533 @@
-use std::num::ParseFloatError;
+use std::{marker::PhantomData, rc::Rc};
 
 use crate::{
-    ast::{Ast, Op},
-    lexer::{Lexer, Token, TokenKind},
+    ast::{Ast, Comparison, Logic, Op},
+    datetime::{Date, DateTime},
+    diagnostic::Diagnostic,
+    lexer::{self, Lexer, Token, TokenKind},
+    number::{self, Numeric},
+    span::Span,
 };
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Ran out of tokens while parsing")]
-    NoMoreTokens,
+    #[error("ran out of tokens while parsing")]
+    NoMoreTokens(Span),
 
-    #[error("Parsing error: {0}")]
-    Parse(String),
+    #[error("parsing error: {message}")]
+    Parse { message: String, span: Span },
+
+    #[error("expected ')', but found {found}")]
+    UnclosedParen {
+        found: String,
+        open: Span,
+        span: Span,
+    },
+
+    #[error("expected ']', but found {found}")]
+    UnclosedBracket {
+        found: String,
+        open: Span,
+        span: Span,
+    },
+
+    #[error("unexpected {found}")]
+    UnexpectedToken {
+        found: String,
+        kind: TokenKind,
+        span: Span,
+    },
+
+    #[error("invalid number: {source}")]
+    Number { source: number::Error, span: Span },
+
+    #[error("invalid date: {message}")]
+    Date { message: String, span: Span },
+
+    #[error("cannot assign to {target}")]
+    InvalidAssignmentTarget { target: String, span: Span },
 
     #[error(transparent)]
-    ParseFloatError(#[from] ParseFloatError),
+    Lex(#[from] lexer::Error),
+}
+
+impl Error {
+    pub fn span(&self) -> Span {
+        match *self {
+            Error::NoMoreTokens(span)
+            | Error::Parse { span, .. }
+            | Error::UnclosedParen { span, .. }
+            | Error::UnclosedBracket { span, .. }
+            | Error::UnexpectedToken { span, .. }
+            | Error::Number { span, .. }
+            | Error::Date { span, .. }
+            | Error::InvalidAssignmentTarget { span, .. } => span,
+            Error::Lex(ref err) => err.span(),
+        }
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::NoMoreTokens(span) => Diagnostic::error("unexpected end of input")
+                .with_label(*span, "expected more input here"),
+            Error::Parse { message, span } => Diagnostic::error(message).with_label(*span, ""),
+            Error::UnclosedParen { found, open, span } => {
+                Diagnostic::error(format!("expected ')', but found {found}"))
+                    .with_label(*span, "expected ')'")
+                    .with_secondary_label(*open, "unclosed '(' opened here")
+            }
+            Error::UnclosedBracket { found, open, span } => {
+                Diagnostic::error(format!("expected ']', but found {found}"))
+                    .with_label(*span, "expected ']'")
+                    .with_secondary_label(*open, "unclosed '[' opened here")
+            }
+            Error::UnexpectedToken { found, kind, span } => {
+                let diagnostic = Diagnostic::error(format!("unexpected {found}"))
+                    .with_label(*span, "expected end of input");
+                if *kind == TokenKind::RightParen {
+                    diagnostic.with_help("this ')' has no matching '('")
+                } else if *kind == TokenKind::RightBracket {
+                    diagnostic.with_help("this ']' has no matching '['")
+                } else {
+                    diagnostic.with_help("is an operator missing before this?")
+                }
+            }
+            Error::Number { source, span } => {
+                Diagnostic::error(format!("invalid number: {source}")).with_label(
+                    *span,
+                    match source {
+                        number::Error::Overflow => "out of range for this type of number",
+                        number::Error::Inexact => "this type of number cannot represent it exactly",
+                        number::Error::DivisionByZero => "",
+                    },
+                )
+            }
+            Error::Date { message, span } => {
+                Diagnostic::error("invalid date").with_label(*span, message)
+            }
+            Error::InvalidAssignmentTarget { target, span } => {
+                Diagnostic::error(format!("cannot assign to `{target}`"))
+                    .with_label(*span, "not a variable name")
+                    .with_help("only names like `x`, or functions like `f(x, y)`, can be defined")
+            }
+            Error::Lex(err) => err.diagnostic(),
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub struct Parser<S: AsRef<str>> {
-    curr_tok: Option<Token>,
-    lexer: Lexer<S>,
+/// Parses expressions whose number literals are of the numeric type `N`.
+pub struct Parser<'a, N = f64> {
+    curr_tok: Option<Token<'a>>,
+    prev_span: Span,
+    lexer: Lexer<'a>,
+    numeric: PhantomData<N>,
 }
 
-impl<S: AsRef<str>> Parser<S> {
-    pub fn new(lexer: Lexer<S>) -> Self {
+impl<'a, N: Numeric> Parser<'a, N> {
+    pub fn new(lexer: Lexer<'a>) -> Self {
         Self {
             lexer,
             curr_tok: None,
+            prev_span: Span::new(0, 0, 1, 1),
+            numeric: PhantomData,
         }
     }
 
-    fn match_it(&mut self) {
-        self.curr_tok = self.lexer.next();
+    /// Moves on to the next token. Once the input is exhausted the current
+    /// token is an `Eof` token.
+    /// Takes the warnings the lexer has produced so far.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.lexer.take_diagnostics()
+    }
+
+    fn match_it(&mut self) -> Result<()> {
+        if let Some(tok) = &self.curr_tok {
+            self.prev_span = tok.span;
+        }
+        self.curr_tok = Some(self.lexer.lex()?);
+
+        Ok(())
+    }
+
+    /// The span of the current token, or an empty span at the start of the
+    /// input if lexing has not started yet.
+    fn curr_span(&self) -> Span {
+        match &self.curr_tok {
+            Some(tok) => tok.span,
+            None => self.prev_span,
+        }
+    }
+
+    /// A span from `start` up to the end of the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.prev_span)
+    }
+
+    /// A human readable description of the current token, for error messages.
+    fn curr_description(&self) -> String {
+        match &self.curr_tok {
+            Some(tok) => tok.to_string(),
+            None => TokenKind::Eof.to_string(),
+        }
     }
 
     fn match_kind(&mut self, expected_kind: TokenKind) -> Result<()> {
         if let Some(tok) = &self.curr_tok {
             if tok.kind != expected_kind {
-                Err(Error::Parse(format!(
-                    "Expected {:#?}, but found {:#?}",
-                    expected_kind, tok.kind
-                )))
+                Err(Error::Parse {
+                    message: format!("expected {}, but found {}", expected_kind, tok.kind),
+                    span: tok.span,
+                })
             } else {
-                self.match_it();
-                Ok(())
+                self.match_it()
             }
         } else {
-            Err(Error::NoMoreTokens)
+            Err(Error::NoMoreTokens(self.curr_span()))
         }
     }
 
-    fn parse_number(&mut self) -> Result<Ast> {
+    /// Number | Imaginary
+    fn parse_number(&mut self) -> Result<Ast<N>> {
         if let Some(tok) = &self.curr_tok {
-            let number = Ast::Number(tok.spelling.parse::<f64>()?);
-            self.match_it();
-            Ok(number)
+            let (spelling, span) = (tok.spelling, tok.span);
+            let imaginary = tok.kind == TokenKind::Imaginary;
+            let digits = if imaginary {
+                &spelling[..spelling.len() - 1]
+            } else {
+                spelling
+            };
+            let value =
+                N::from_spelling(digits).map_err(|source| Error::Number { source, span })?;
+            self.match_it()?;
+
+            Ok(if imaginary {
+                Ast::Imaginary { value, span }
+            } else {
+                Ast::Number { value, span }
+            })
         } else {
-            Err(Error::NoMoreTokens)
+            Err(Error::NoMoreTokens(self.curr_span()))
         }
     }
 
-    fn parse_operator(&mut self) -> Result<Op> {
+    /// Date, which is a datetime if it has a time
+    fn parse_date(&mut self) -> Result<Ast<N>> {
         if let Some(tok) = &self.curr_tok {
-            match tok.kind {
-                TokenKind::Plus | TokenKind::Minus | TokenKind::Asterisk | TokenKind::Slash => {
-                    let op = match tok.kind {
-                        TokenKind::Plus => Op::Add,
-                        TokenKind::Minus => Op::Sub,
-                        TokenKind::Asterisk => Op::Mul,
-                        TokenKind::Slash => Op::Div,
-                        _ => unreachable!(),
-                    };
-                    self.match_it();
+            let (spelling, span) = (tok.spelling.trim_start_matches('@'), tok.span);
+            let ast = if spelling.contains('T') {
+                spelling
+                    .parse::<DateTime>()
+                    .map(|value| Ast::DateTime { value, span })
+            } else {
+                spelling
+                    .parse::<Date>()
+                    .map(|value| Ast::Date { value, span })
+            };
+            let ast = ast.map_err(|message| Error::Date { message, span })?;
+            self.match_it()?;
+
+            Ok(ast)
+        } else {
+            Err(Error::NoMoreTokens(self.curr_span()))
+        }
+    }
+
+    /// Identifier Args?
+    fn parse_name(&mut self) -> Result<Ast<N>> {
+        if let Some(tok) = &self.curr_tok {
+            let (name, start) = (tok.spelling.to_owned(), tok.span);
+            self.match_it()?;
+
+            match &self.curr_tok {
+                Some(tok) if tok.kind == TokenKind::LeftParen => {
+                    let args = self.parse_arguments()?;
 
-                    Ok(op)
+                    Ok(Ast::Call {
+                        name,
+                        args,
+                        span: self.span_from(start),
+                    })
                 }
-                _ => Err(Error::Parse(format!(
-                    "Expected an operator, but found {:#?}",
-                    tok.kind
-                ))),
+                _ => Ok(Ast::Variable { name, span: start }),
             }
         } else {
-            Err(Error::NoMoreTokens)
+            Err(Error::NoMoreTokens(self.curr_span()))
+        }
+    }
+
+    /// Args <- '(' (K (',' K)*)? ')'
+    fn parse_arguments(&mut self) -> Result<Vec<Ast<N>>> {
+        let open = self.curr_span();
+        self.match_kind(TokenKind::LeftParen)?;
+        let args = self.parse_elements(TokenKind::RightParen)?;
+        self.match_closing_paren(open)?;
+
+        Ok(args)
+    }
+
+    /// List <- '[' (K (',' K)*)? ']'
+    fn parse_list(&mut self) -> Result<Ast<N>> {
+        let open = self.curr_span();
+        self.match_kind(TokenKind::LeftBracket)?;
+        let elements = self.parse_elements(TokenKind::RightBracket)?;
+        self.match_closing_bracket(open)?;
+
+        Ok(Ast::List {
+            elements,
+            span: self.span_from(open),
+        })
+    }
+
+    /// (K (',' K)*)?, up to the `close` token that ends them.
+    fn parse_elements(&mut self, close: TokenKind) -> Result<Vec<Ast<N>>> {
+        let mut elements = Vec::new();
+        if self.curr_tok.as_ref().is_some_and(|tok| tok.kind != close) {
+            elements.push(self.parse_conditional()?);
+            while self
+                .curr_tok
+                .as_ref()
+                .is_some_and(|tok| tok.kind == TokenKind::Comma)
+            {
+                self.match_it()?;
+                elements.push(self.parse_conditional()?);
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Matches the ')' closing the '(' at `open`.
+    fn match_closing_paren(&mut self, open: Span) -> Result<()> {
+        match self.match_kind(TokenKind::RightParen) {
+            Err(Error::Lex(err)) => Err(Error::Lex(err)),
+            Err(err) => Err(Error::UnclosedParen {
+                found: self.curr_description(),
+                open,
+                span: err.span(),
+            }),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Matches the ']' closing the '[' at `open`.
+    fn match_closing_bracket(&mut self, open: Span) -> Result<()> {
+        match self.match_kind(TokenKind::RightBracket) {
+            Err(Error::Lex(err)) => Err(Error::Lex(err)),
+            Err(err) => Err(Error::UnclosedBracket {
+                found: self.curr_description(),
+                open,
+                span: err.span(),
+            }),
+            Ok(()) => Ok(()),
         }
     }
 
-    /// Primary <- Number | '(' E ')'
-    fn parse_primary(&mut self) -> Result<Ast> {
+    fn parse_operator(&mut self) -> Result<Op> {
+        if let Some(tok) = &self.curr_tok {
+            let op = match tok.kind {
+                TokenKind::Plus => Op::Add,
+                TokenKind::Minus => Op::Sub,
+                TokenKind::Asterisk => Op::Mul,
+                TokenKind::Slash => Op::Div,
+                TokenKind::Caret | TokenKind::DoubleAsterisk => Op::Pow,
+                TokenKind::Percent => Op::Rem,
+                TokenKind::Mod => Op::Mod,
+                TokenKind::DoubleSlash => Op::FloorDiv,
+                _ => {
+                    return Err(Error::Parse {
+                        message: format!("expected an operator, but found {}", tok.kind),
+                        span: tok.span,
+                    })
+                }
+            };
+            self.match_it()?;
+
+            Ok(op)
+        } else {
+            Err(Error::NoMoreTokens(self.curr_span()))
+        }
+    }
+
+    /// Primary <- Number | Imaginary | Date | 'true' | 'false'
+    ///          | Identifier Args? | List | '(' K ')'
+    fn parse_primary(&mut self) -> Result<Ast<N>> {
         if let Some(tok) = &self.curr_tok {
             match tok.kind {
-                TokenKind::Number => self.parse_number(),
+                TokenKind::Number | TokenKind::Imaginary => self.parse_number(),
+                TokenKind::Date => self.parse_date(),
+                TokenKind::True | TokenKind::False => {
+                    let (value, span) = (tok.kind == TokenKind::True, tok.span);
+                    self.match_it()?;
+                    Ok(Ast::Bool { value, span })
+                }
+                TokenKind::Identifier => self.parse_name(),
+                TokenKind::LeftBracket => self.parse_list(),
                 TokenKind::LeftParen => {
-                    self.match_it();
-                    let e = self.parse_expression()?;
-                    self.match_kind(TokenKind::RightParen)?;
+                    let open = tok.span;
+                    self.match_it()?;
+                    let e = self.parse_conditional()?;
+                    self.match_closing_paren(open)?;
                     Ok(e)
                 }
-                _ => Err(Error::Parse(format!(
-                    "Expected number or parenthesised expression. Found: {:#?}",
-                    tok.kind
-                ))),
+                _ => Err(Error::Parse {
+                    message: format!(
+                        "expected number, name, list or parenthesised expression, but found {}",
+                        tok.kind
+                    ),
+                    span: tok.span,
+                }),
             }
         } else {
-            Err(Error::NoMoreTokens)
+            Err(Error::NoMoreTokens(self.curr_span()))
         }
     }
 
-    /// F <- ('+' | '- ') Primary
-    fn parse_factor(&mut self) -> Result<Ast> {
+    /// P <- Primary ('[' K ']' | Args)* (('^' | '**') F)?
+    ///
+    /// The exponent is itself a factor, which makes exponentiation right
+    /// associative and lets it take a sign, as in `2^-1`. An index binds
+    /// tighter still, so `v[0]^2` squares an element. Arguments only follow
+    /// a call or an index, and call the function it gives, as in
+    /// `adder(2)(3)` or `fs[0](3)`.
+    fn parse_power(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let mut base = self.parse_primary()?;
+
+        while let Some(tok) = &self.curr_tok {
+            match tok.kind {
+                TokenKind::LeftBracket => {
+                    let open = tok.span;
+                    self.match_it()?;
+                    let index = self.parse_conditional()?;
+                    self.match_closing_bracket(open)?;
+
+                    base = Ast::Index {
+                        value: Box::new(base),
+                        index: Box::new(index),
+                        span: self.span_from(start),
+                    };
+                }
+                TokenKind::LeftParen
+                    if matches!(
+                        base,
+                        Ast::Call { .. } | Ast::Apply { .. } | Ast::Index { .. }
+                    ) =>
+                {
+                    let args = self.parse_arguments()?;
+
+                    base = Ast::Apply {
+                        function: Box::new(base),
+                        args,
+                        span: self.span_from(start),
+                    };
+                }
+                _ => break,
+            }
+        }
+
+        match &self.curr_tok {
+            Some(tok) if matches!(tok.kind, TokenKind::Caret | TokenKind::DoubleAsterisk) => {
+                let op = self.parse_operator()?;
+                let exponent = self.parse_factor()?;
+
+                Ok(Ast::BinaryExpr {
+                    lhs: Box::new(base),
+                    op,
+                    rhs: Box::new(exponent),
+                    span: self.span_from(start),
+                })
+            }
+            _ => Ok(base),
+        }
+    }
+
+    /// F <- ('+' | '- ') P | P
+    ///
+    /// A sign applies to the whole power, so `-2^2` is `-(2^2)`.
+    fn parse_factor(&mut self) -> Result<Ast<N>> {
         if let Some(tok) = &self.curr_tok {
             match tok.kind {
                 TokenKind::Plus | TokenKind::Minus => {
+                    let start = self.curr_span();
                     let op = self.parse_operator()?;
-                    let t = self.parse_primary()?;
+                    let t = self.parse_power()?;
 
                     Ok(Ast::UnaryExpr {
                         op,
                         rhs: Box::new(t),
+                        span: self.span_from(start),
                     })
                 }
-                _ => self.parse_primary(),
+                _ => self.parse_power(),
             }
         } else {
-            Err(Error::NoMoreTokens)
+            Err(Error::NoMoreTokens(self.curr_span()))
         }
     }
 
-    /// T <- F (('*' / '/') F)*
-    fn parse_term(&mut self) -> Result<Ast> {
+    /// J <- F (&Identifier P)* (&Number J)?
+    ///
+    /// A factor followed by names is their product, as in `3 m` or `2 pi`.
+    /// The product binds tighter than `*` and `/`, so that `3 m / 2 s` is a
+    /// speed. A number after such a product starts another, and the two
+    /// make a compound quantity, as in `3 h 20 min`.
+    fn parse_juxtaposition(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
         let mut f1 = self.parse_factor()?;
+        let mut named = false;
+
+        while let Some(tok) = &self.curr_tok {
+            if tok.kind != TokenKind::Identifier {
+                break;
+            }
+            let f2 = self.parse_power()?;
+            named = true;
+
+            f1 = Ast::BinaryExpr {
+                lhs: Box::new(f1),
+                op: Op::Mul,
+                rhs: Box::new(f2),
+                span: self.span_from(start),
+            };
+        }
+
+        match &self.curr_tok {
+            Some(tok) if named && tok.kind == TokenKind::Number => {
+                let mut parts = vec![f1];
+                match self.parse_juxtaposition()? {
+                    Ast::Compound { parts: rest, .. } => parts.extend(rest),
+                    rest => parts.push(rest),
+                }
+                Ok(Ast::Compound {
+                    parts,
+                    span: self.span_from(start),
+                })
+            }
+            _ => Ok(f1),
+        }
+    }
+
+    /// T <- J (('*' | '/' | '%' | 'mod' | '//') J)*
+    fn parse_term(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let mut f1 = self.parse_juxtaposition()?;
 
         while let Some(tok) = &self.curr_tok {
             match tok.kind {
-                TokenKind::Asterisk | TokenKind::Slash => {
+                TokenKind::Asterisk
+                | TokenKind::Slash
+                | TokenKind::Percent
+                | TokenKind::Mod
+                | TokenKind::DoubleSlash => {
                     let op = self.parse_operator()?;
-                    let f2 = self.parse_factor()?;
+                    let f2 = self.parse_juxtaposition()?;
 
                     f1 = Ast::BinaryExpr {
                         lhs: Box::new(f1),
                         op,
                         rhs: Box::new(f2),
+                        span: self.span_from(start),
                     };
                 }
                 _ => break,
@@ -151,7 +537,8 @@ impl<S: AsRef<str>> Parser<S> {
     }
 
     /// E <- T (('+' | '-') T)*
-    fn parse_expression(&mut self) -> Result<Ast> {
+    fn parse_expression(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
         let mut t1 = self.parse_term()?;
 
         while let Some(tok) = &self.curr_tok {
@@ -164,6 +551,7 @@ impl<S: AsRef<str>> Parser<S> {
                         lhs: Box::new(t1),
                         op,
                         rhs: Box::new(t2),
+                        span: self.span_from(start),
                     };
                 }
                 _ => break,
@@ -173,15 +561,344 @@ impl<S: AsRef<str>> Parser<S> {
         Ok(t1)
     }
 
+    /// C <- E (('to' | 'in') E)*
+    fn parse_conversion(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let mut e1 = self.parse_expression()?;
+
+        while let Some(tok) = &self.curr_tok {
+            match tok.kind {
+                TokenKind::To | TokenKind::In => {
+                    self.match_it()?;
+                    let e2 = self.parse_expression()?;
+
+                    e1 = Ast::Convert {
+                        value: Box::new(e1),
+                        target: Box::new(e2),
+                        span: self.span_from(start),
+                    };
+                }
+                _ => break,
+            }
+        }
+        Ok(e1)
+    }
+
+    /// G <- C ('..' C)?
+    fn parse_range(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let from = self.parse_conversion()?;
+
+        match &self.curr_tok {
+            Some(tok) if tok.kind == TokenKind::DoubleDot => {
+                self.match_it()?;
+                let to = self.parse_conversion()?;
+
+                Ok(Ast::Range {
+                    from: Box::new(from),
+                    to: Box::new(to),
+                    span: self.span_from(start),
+                })
+            }
+            _ => Ok(from),
+        }
+    }
+
+    /// R <- G (('==' | '!=' | '<' | '<=' | '>' | '>=') G)?
+    ///
+    /// Comparisons do not chain: `a < b < c` is an error rather than a
+    /// comparison of a boolean with `c`.
+    fn parse_comparison(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let lhs = self.parse_range()?;
+
+        let Some(op) = self.curr_comparison() else {
+            return Ok(lhs);
+        };
+        self.match_it()?;
+        let rhs = self.parse_range()?;
+
+        if self.curr_comparison().is_some() {
+            return Err(Error::Parse {
+                message: "comparisons cannot be chained; join them with 'and'".to_owned(),
+                span: self.curr_span(),
+            });
+        }
+        Ok(Ast::Compare {
+            lhs: Box::new(lhs),
+            op,
+            rhs: Box::new(rhs),
+            span: self.span_from(start),
+        })
+    }
+
+    fn curr_comparison(&self) -> Option<Comparison> {
+        match self.curr_tok.as_ref()?.kind {
+            TokenKind::DoubleEquals => Some(Comparison::Eq),
+            TokenKind::BangEquals => Some(Comparison::Ne),
+            TokenKind::Less => Some(Comparison::Lt),
+            TokenKind::LessEquals => Some(Comparison::Le),
+            TokenKind::Greater => Some(Comparison::Gt),
+            TokenKind::GreaterEquals => Some(Comparison::Ge),
+            _ => None,
+        }
+    }
+
+    /// N <- 'not' N | R
+    fn parse_not(&mut self) -> Result<Ast<N>> {
+        match &self.curr_tok {
+            Some(tok) if tok.kind == TokenKind::Not => {
+                let start = tok.span;
+                self.match_it()?;
+                let rhs = self.parse_not()?;
+
+                Ok(Ast::Not {
+                    rhs: Box::new(rhs),
+                    span: self.span_from(start),
+                })
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    /// A <- N ('and' N)*
+    fn parse_and(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let mut lhs = self.parse_not()?;
+
+        while self
+            .curr_tok
+            .as_ref()
+            .is_some_and(|tok| tok.kind == TokenKind::And)
+        {
+            self.match_it()?;
+            let rhs = self.parse_not()?;
+
+            lhs = Ast::Logical {
+                lhs: Box::new(lhs),
+                op: Logic::And,
+                rhs: Box::new(rhs),
+                span: self.span_from(start),
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// O <- A ('or' A)*
+    fn parse_or(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let mut lhs = self.parse_and()?;
+
+        while self
+            .curr_tok
+            .as_ref()
+            .is_some_and(|tok| tok.kind == TokenKind::Or)
+        {
+            self.match_it()?;
+            let rhs = self.parse_and()?;
+
+            lhs = Ast::Logical {
+                lhs: Box::new(lhs),
+                op: Logic::Or,
+                rhs: Box::new(rhs),
+                span: self.span_from(start),
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// K <- Lambda | 'if' K 'then' K 'else' K | O ('?' K ':' K)?
+    ///
+    /// Both forms chain to the right, as in `a ? b : c ? d : e`.
+    fn parse_conditional(&mut self) -> Result<Ast<N>> {
+        if self.at_lambda() {
+            return self.parse_lambda();
+        }
+        let start = self.curr_span();
+
+        let (condition, separator) = match &self.curr_tok {
+            Some(tok) if tok.kind == TokenKind::If => {
+                self.match_it()?;
+                let condition = self.parse_conditional()?;
+                self.match_kind(TokenKind::Then)?;
+                (condition, TokenKind::Else)
+            }
+            _ => {
+                let condition = self.parse_or()?;
+                match &self.curr_tok {
+                    Some(tok) if tok.kind == TokenKind::Question => self.match_it()?,
+                    _ => return Ok(condition),
+                }
+                (condition, TokenKind::Colon)
+            }
+        };
+        let value = self.parse_conditional()?;
+        self.match_kind(separator)?;
+        let otherwise = self.parse_conditional()?;
+
+        Ok(Ast::If {
+            condition: Box::new(condition),
+            value: Box::new(value),
+            otherwise: Box::new(otherwise),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Lambda <- (Identifier | Params) '->' K
+    ///
+    /// The body extends as far to the right as it can, as that of an `if`
+    /// does, so `x -> x + 1` is a function of `x` that adds one.
+    fn parse_lambda(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let params = match &self.curr_tok {
+            Some(tok) if tok.kind == TokenKind::LeftParen => self.parse_arguments()?,
+            _ => vec![self.parse_name()?],
+        };
+        let params = Self::parameters(params)?;
+        self.match_kind(TokenKind::Arrow)?;
+        let body = self.parse_conditional()?;
+
+        Ok(Ast::Lambda {
+            params,
+            body: Rc::new(body),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Whether a lambda starts at the current token, which takes looking
+    /// past its parameters for the '->'.
+    fn at_lambda(&self) -> bool {
+        let Some(tok) = &self.curr_tok else {
+            return false;
+        };
+        let mut lexer = self.lexer.clone();
+        let mut next = move || lexer.lex().map_or(TokenKind::Eof, |tok| tok.kind);
+
+        match tok.kind {
+            TokenKind::Identifier => next() == TokenKind::Arrow,
+            TokenKind::LeftParen => {
+                let mut kind = next();
+                if kind != TokenKind::RightParen {
+                    loop {
+                        if kind != TokenKind::Identifier {
+                            return false;
+                        }
+                        kind = next();
+                        if kind != TokenKind::Comma {
+                            break;
+                        }
+                        kind = next();
+                    }
+                }
+                kind == TokenKind::RightParen && next() == TokenKind::Arrow
+            }
+            _ => false,
+        }
+    }
+
+    /// S <- Identifier '=' S | Identifier Params '=' K | K
+    ///
+    /// The left hand side of an assignment is parsed as an expression and
+    /// then checked to be a plain name, or a call whose arguments are plain
+    /// names for a function definition, which saves looking ahead.
+    /// Assignments chain to the right, as in `x = y = 0`.
+    fn parse_statement(&mut self) -> Result<Ast<N>> {
+        let start = self.curr_span();
+        let e = self.parse_conditional()?;
+
+        match &self.curr_tok {
+            Some(tok) if tok.kind == TokenKind::Equals => match e {
+                Ast::Variable { name, .. } => {
+                    self.match_it()?;
+                    let value = self.parse_statement()?;
+
+                    Ok(Ast::Assign {
+                        name,
+                        value: Box::new(value),
+                        span: self.span_from(start),
+                    })
+                }
+                Ast::Call { name, args, .. } => {
+                    let params = Self::parameters(args)?;
+                    self.match_it()?;
+                    let body = self.parse_conditional()?;
+
+                    Ok(Ast::FunctionDef {
+                        name,
+                        params,
+                        body: Rc::new(body),
+                        span: self.span_from(start),
+                    })
+                }
+                _ => Err(Error::InvalidAssignmentTarget {
+                    target: e.to_string(),
+                    span: e.span(),
+                }),
+            },
+            _ => Ok(e),
+        }
+    }
+
+    /// Params <- '(' (Identifier (',' Identifier)*)? ')'
+    ///
+    /// The parameters of a definition or lambda, from the arguments they
+    /// were parsed as. Each must be a distinct name.
+    fn parameters(args: Vec<Ast<N>>) -> Result<Vec<String>> {
+        let mut params: Vec<String> = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg {
+                Ast::Variable { name, span } if params.contains(&name) => {
+                    return Err(Error::Parse {
+                        message: format!("parameter '{name}' is named twice"),
+                        span,
+                    });
+                }
+                Ast::Variable { name, .. } => params.push(name),
+                _ => {
+                    return Err(Error::InvalidAssignmentTarget {
+                        target: arg.to_string(),
+                        span: arg.span(),
+                    })
+                }
+            }
+        }
+        Ok(params)
+    }
+
     /// Grammar:
     ///
+    /// Input <- S Eof
+    /// S <- Identifier '=' S | Identifier Params '=' K | K
+    /// K <- Lambda | 'if' K 'then' K 'else' K | O ('?' K ':' K)?
+    /// Lambda <- (Identifier | Params) '->' K
+    /// O <- A ('or' A)*
+    /// A <- N ('and' N)*
+    /// N <- 'not' N | R
+    /// R <- G (('==' | '!=' | '<' | '<=' | '>' | '>=') G)?
+    /// G <- C ('..' C)?
+    /// C <- E (('to' | 'in') E)*
     /// E <- T (('+' | '-) T)*
-    /// T <- F (('*' | '/') F)*
-    /// F <- ('+' | '-') Primary
-    /// Primary <- Number | '(' E ')'
+    /// T <- J (('*' | '/' | '%' | 'mod' | '//') J)*
+    /// J <- F (&Identifier P)* (&Number J)?
+    /// F <- ('+' | '-') P | P
+    /// P <- Primary ('[' K ']' | Args)* (('^' | '**') F)?
+    /// Primary <- Number | Imaginary | Date | 'true' | 'false'
+    ///          | Identifier Args? | List | '(' K ')'
+    /// Args <- '(' (K (',' K)*)? ')'
+    /// List <- '[' (K (',' K)*)? ']'
+    /// Params <- '(' (Identifier (',' Identifier)*)? ')'
     ///
-    pub fn parse(&mut self) -> Result<Ast> {
-        self.match_it();
-        self.parse_expression()
+    pub fn parse(&mut self) -> Result<Ast<N>> {
+        self.match_it()?;
+        let e = self.parse_statement()?;
+
+        match self.curr_tok.take() {
+            Some(tok) if tok.kind != TokenKind::Eof => Err(Error::UnexpectedToken {
+                found: tok.to_string(),
+                kind: tok.kind,
+                span: tok.span,
+            }),
+            _ => Ok(e),
+        }
     }
 }
@@ -0,0 +1,604 @@
+pub mod currency;
+
+use std::{collections::BTreeMap, fmt};
+
+use currency::Rates;
+
+/// The SI base units, and `¤` for money, in the order of a [`Dimension`]'s
+/// exponents.
+const BASE_UNITS: [&str; 8] = ["m", "kg", "s", "A", "K", "mol", "cd", "¤"];
+
+/// What a quantity measures, as the exponents of the seven SI base
+/// dimensions: length, mass, time, current, temperature, amount of
+/// substance and luminous intensity, and of money. Speed is
+/// length¹·time⁻¹.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Dimension([i32; 8]);
+
+impl Dimension {
+    /// The dimension of plain numbers.
+    pub const NONE: Dimension = Dimension([0; 8]);
+    pub const LENGTH: Dimension = Dimension([1, 0, 0, 0, 0, 0, 0, 0]);
+    pub const MASS: Dimension = Dimension([0, 1, 0, 0, 0, 0, 0, 0]);
+    pub const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0, 0, 0]);
+    pub const CURRENT: Dimension = Dimension([0, 0, 0, 1, 0, 0, 0, 0]);
+    pub const TEMPERATURE: Dimension = Dimension([0, 0, 0, 0, 1, 0, 0, 0]);
+    pub const AMOUNT: Dimension = Dimension([0, 0, 0, 0, 0, 1, 0, 0]);
+    pub const LUMINOUS_INTENSITY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1, 0]);
+    pub const CURRENCY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0, 1]);
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+
+    pub fn div(&self, other: &Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] - other.0[i]))
+    }
+
+    pub fn pow(&self, exp: i32) -> Self {
+        Self(self.0.map(|e| e * exp))
+    }
+}
+
+/// Displays the dimension in SI base units, as in `kg*m/s^2`.
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let powers = BASE_UNITS
+            .iter()
+            .zip(self.0)
+            .map(|(&symbol, power)| (symbol, power));
+        write_powers(f, powers)
+    }
+}
+
+/// Writes a product of powers as `a*b^2/(c*d)`, or `1` if it is empty.
+fn write_powers<'a>(
+    f: &mut fmt::Formatter<'_>,
+    powers: impl Iterator<Item = (&'a str, i32)> + Clone,
+) -> fmt::Result {
+    let product = |positive: bool| {
+        powers
+            .clone()
+            .filter(|&(_, power)| power != 0 && (power > 0) == positive)
+            .map(|(symbol, power)| match power.abs() {
+                1 => symbol.to_owned(),
+                power => format!("{symbol}^{power}"),
+            })
+            .collect::<Vec<_>>()
+    };
+    let (numer, denom) = (product(true), product(false));
+
+    match (numer.is_empty(), denom.len()) {
+        (true, 0) => write!(f, "1"),
+        (_, 0) => write!(f, "{}", numer.join("*")),
+        (numer_is_empty, n) => {
+            let numer = if numer_is_empty {
+                "1".to_owned()
+            } else {
+                numer.join("*")
+            };
+            if n == 1 {
+                write!(f, "{numer}/{}", denom[0])
+            } else {
+                write!(f, "{numer}/({})", denom.join("*"))
+            }
+        }
+    }
+}
+
+/// A unit with a name, such as `km` or `h`: `scale` of the SI base units of
+/// its dimension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedUnit {
+    pub symbol: String,
+    pub scale: f64,
+    pub dimension: Dimension,
+}
+
+/// A unit as a product of powers of named units, such as `km/h`, kept as
+/// written so that results are shown in the units they were given in.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Unit {
+    factors: Vec<(NamedUnit, i32)>,
+}
+
+impl Unit {
+    /// The unit of plain numbers.
+    pub fn one() -> Self {
+        Self::default()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.factors
+            .iter()
+            .fold(Dimension::NONE, |dimension, (unit, power)| {
+                dimension.mul(&unit.dimension.pow(*power))
+            })
+    }
+
+    /// Converts `value` in this unit to the SI base units. The scales of the
+    /// numerator and of the denominator are applied separately, so that
+    /// `72 km/h` is exactly `72 * 1000 / 3600` m/s.
+    pub fn to_si(&self, value: f64) -> f64 {
+        let (numer, denom) = self.scales();
+        value * numer / denom
+    }
+
+    /// Converts `value` in SI base units to this unit.
+    pub fn from_si(&self, value: f64) -> f64 {
+        let (numer, denom) = self.scales();
+        value * denom / numer
+    }
+
+    fn scales(&self) -> (f64, f64) {
+        self.factors
+            .iter()
+            .fold((1.0, 1.0), |(numer, denom), (unit, power)| {
+                let scale = unit.scale.powi(power.abs());
+                if *power > 0 {
+                    (numer * scale, denom)
+                } else {
+                    (numer, denom * scale)
+                }
+            })
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut factors = self.factors.clone();
+        for (unit, power) in &other.factors {
+            match factors.iter_mut().find(|(u, _)| u.symbol == unit.symbol) {
+                Some((_, p)) => *p += power,
+                None => factors.push((unit.clone(), *power)),
+            }
+        }
+        factors.retain(|(_, power)| *power != 0);
+        Self { factors }
+    }
+
+    pub fn div(&self, other: &Self) -> Self {
+        self.mul(&other.pow(-1))
+    }
+
+    pub fn pow(&self, exp: i32) -> Self {
+        let factors = if exp == 0 {
+            Vec::new()
+        } else {
+            self.factors
+                .iter()
+                .map(|(unit, power)| (unit.clone(), power * exp))
+                .collect()
+        };
+        Self { factors }
+    }
+
+    /// The currencies in the unit, with their powers, in symbol order.
+    pub fn currencies(&self) -> Vec<(&str, i32)> {
+        let mut currencies = self
+            .factors
+            .iter()
+            .filter(|(unit, _)| unit.dimension == Dimension::CURRENCY)
+            .map(|(unit, power)| (unit.symbol.as_str(), *power))
+            .collect::<Vec<_>>();
+        currencies.sort_unstable();
+        currencies
+    }
+
+    /// The `n`th root, if every power in the unit is divisible by `n`, as
+    /// in the square root of `m^2`.
+    pub fn root(&self, n: i32) -> Option<Self> {
+        self.factors
+            .iter()
+            .map(|(unit, power)| (power % n == 0).then(|| (unit.clone(), power / n)))
+            .collect::<Option<_>>()
+            .map(|factors| Self { factors })
+    }
+}
+
+impl From<NamedUnit> for Unit {
+    fn from(unit: NamedUnit) -> Self {
+        Self {
+            factors: vec![(unit, 1)],
+        }
+    }
+}
+
+/// Displays the unit as in `km/h` or `kg*m/s^2`.
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let powers = self
+            .factors
+            .iter()
+            .map(|(unit, power)| (unit.symbol.as_str(), *power));
+        write_powers(f, powers)
+    }
+}
+
+/// A number of some unit, such as `72 km/h`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+
+    /// The quantity in SI base units.
+    pub fn to_si(&self) -> f64 {
+        self.unit.to_si(self.value)
+    }
+
+    /// The quantity expressed in `unit`, or `None` if it measures something
+    /// else.
+    pub fn convert(&self, unit: &Unit) -> Option<Self> {
+        if self.dimension() != unit.dimension() {
+            return None;
+        }
+        Some(Self::new(unit.from_si(self.to_si()), unit.clone()))
+    }
+
+    /// The sum, in the unit of `self`, or `None` if the quantities measure
+    /// different things. Amounts in different currencies are not added
+    /// either, since that takes an exchange rate the caller should see.
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        if self.unit.currencies() != other.unit.currencies() {
+            return None;
+        }
+        let other = other.convert(&self.unit)?;
+        Some(Self::new(self.value + other.value, self.unit.clone()))
+    }
+
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        self.add(&other.neg())
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(-self.value, self.unit.clone())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::new(self.value * other.value, self.unit.mul(&other.unit))
+    }
+
+    pub fn div(&self, other: &Self) -> Self {
+        Self::new(self.value / other.value, self.unit.div(&other.unit))
+    }
+
+    /// Raises the quantity to the power `exp`, or returns `None` if that
+    /// leaves its unit with a fractional power, as `(1 m)^0.5` would.
+    pub fn pow(&self, exp: f64) -> Option<Self> {
+        let value = self.value.powf(exp);
+        if self.unit.is_one() {
+            return Some(Self::new(value, Unit::one()));
+        }
+        if exp.fract() == 0.0 && exp.abs() <= f64::from(i32::MAX) {
+            return Some(Self::new(value, self.unit.pow(exp as i32)));
+        }
+        // A root, as in `(4 m^2)^0.5`.
+        let n = (1.0 / exp).round();
+        if n.abs() <= f64::from(i32::MAX) && 1.0 / n == exp {
+            return self.unit.root(n as i32).map(|unit| Self::new(value, unit));
+        }
+        None
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Self::new(value, Unit::one())
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.unit.is_one() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "{} {}", self.value, self.unit)
+        }
+    }
+}
+
+/// The SI prefixes, with `u` as well as `µ` for micro.
+const PREFIXES: &[(&str, f64)] = &[
+    ("Q", 1e30),
+    ("R", 1e27),
+    ("Y", 1e24),
+    ("Z", 1e21),
+    ("E", 1e18),
+    ("P", 1e15),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("da", 1e1),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("µ", 1e-6),
+    ("u", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+    ("a", 1e-18),
+    ("z", 1e-21),
+    ("y", 1e-24),
+    ("r", 1e-27),
+    ("q", 1e-30),
+];
+
+/// Whether a unit can take an SI prefix, as `m` does in `km`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Prefixes {
+    Allowed,
+    NotAllowed,
+}
+
+/// A unit in the database, as it is defined before any prefix is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitDef {
+    pub scale: f64,
+    pub dimension: Dimension,
+    pub prefixes: Prefixes,
+    pub description: &'static str,
+}
+
+/// The units known to an evaluator, keyed by symbol. Names are resolved
+/// against these after constants and variables, so a variable called `m`
+/// hides the metre.
+#[derive(Debug, Clone)]
+pub struct Units {
+    units: BTreeMap<String, UnitDef>,
+    /// The date of the exchange rates the currencies were defined with.
+    rates_date: Option<String>,
+}
+
+impl Units {
+    /// A database without any units.
+    pub fn empty() -> Self {
+        Self {
+            units: BTreeMap::new(),
+            rates_date: None,
+        }
+    }
+
+    /// Defines the unit `symbol` as `scale` times the SI base units of
+    /// `dimension`, replacing any unit already there.
+    pub fn define(
+        &mut self,
+        symbol: impl Into<String>,
+        scale: f64,
+        dimension: Dimension,
+        prefixes: Prefixes,
+        description: &'static str,
+    ) {
+        self.units.insert(
+            symbol.into(),
+            UnitDef {
+                scale,
+                dimension,
+                prefixes,
+                description,
+            },
+        );
+    }
+
+    /// Looks up `symbol`, which is either a unit's own symbol or an SI
+    /// prefix followed by the symbol of a unit that takes prefixes. A unit's
+    /// own symbol wins, so `min` is the minute rather than a milli-inch.
+    pub fn get(&self, symbol: &str) -> Option<NamedUnit> {
+        let named = |def: &UnitDef, scale: f64| NamedUnit {
+            symbol: symbol.to_owned(),
+            scale: def.scale * scale,
+            dimension: def.dimension,
+        };
+
+        if let Some(def) = self.units.get(symbol) {
+            return Some(named(def, 1.0));
+        }
+        PREFIXES.iter().find_map(|&(prefix, scale)| {
+            let def = self.units.get(symbol.strip_prefix(prefix)?)?;
+            (def.prefixes == Prefixes::Allowed).then(|| named(def, scale))
+        })
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.get(symbol).is_some()
+    }
+
+    /// Defines a unit for each currency in `rates`, replacing the currencies
+    /// of any rates set before. Each is valued in the base currency.
+    pub fn set_rates(&mut self, rates: &Rates) {
+        self.units
+            .retain(|_, def| def.dimension != Dimension::CURRENCY);
+        for (code, rate) in &rates.rates {
+            self.define(
+                code.as_str(),
+                1.0 / rate,
+                Dimension::CURRENCY,
+                Prefixes::NotAllowed,
+                "currency",
+            );
+        }
+        self.rates_date = Some(rates.date.clone());
+    }
+
+    /// The date of the exchange rates, if any have been set.
+    pub fn rates_date(&self) -> Option<&str> {
+        self.rates_date.as_deref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &UnitDef)> {
+        self.units
+            .iter()
+            .map(|(symbol, def)| (symbol.as_str(), def))
+    }
+}
+
+impl Default for Units {
+    /// The SI base and derived units, and common non-SI and imperial units.
+    fn default() -> Self {
+        use Prefixes::*;
+
+        const L: Dimension = Dimension::LENGTH;
+        const M: Dimension = Dimension::MASS;
+        const T: Dimension = Dimension::TIME;
+        let area = L.pow(2);
+        let volume = L.pow(3);
+        let speed = L.div(&T);
+        let force = M.mul(&L).div(&T.pow(2));
+        let energy = force.mul(&L);
+        let power = energy.div(&T);
+        let charge = Dimension::CURRENT.mul(&T);
+
+        let mut units = Units::empty();
+
+        units.define("m", 1.0, L, Allowed, "metre");
+        units.define("g", 1e-3, M, Allowed, "gram");
+        units.define("s", 1.0, T, Allowed, "second");
+        units.define("A", 1.0, Dimension::CURRENT, Allowed, "ampere");
+        units.define("K", 1.0, Dimension::TEMPERATURE, Allowed, "kelvin");
+        units.define("mol", 1.0, Dimension::AMOUNT, Allowed, "mole");
+        units.define("cd", 1.0, Dimension::LUMINOUS_INTENSITY, Allowed, "candela");
+
+        units.define("Hz", 1.0, T.pow(-1), Allowed, "hertz");
+        units.define("N", 1.0, force, Allowed, "newton");
+        units.define("Pa", 1.0, force.div(&area), Allowed, "pascal");
+        units.define("J", 1.0, energy, Allowed, "joule");
+        units.define("W", 1.0, power, Allowed, "watt");
+        units.define("C", 1.0, charge, Allowed, "coulomb");
+        units.define("V", 1.0, power.div(&Dimension::CURRENT), Allowed, "volt");
+        units.define("L", 1e-3, volume, Allowed, "litre");
+        units.define("t", 1e3, M, NotAllowed, "tonne");
+        units.define("ha", 1e4, area, NotAllowed, "hectare");
+        units.define("bar", 1e5, force.div(&area), Allowed, "bar");
+        units.define("eV", 1.602_176_634e-19, energy, Allowed, "electronvolt");
+        units.define("cal", 4.184, energy, Allowed, "thermochemical calorie");
+
+        units.define("min", 60.0, T, NotAllowed, "minute");
+        units.define("h", 3600.0, T, NotAllowed, "hour");
+        units.define("day", 86_400.0, T, NotAllowed, "day");
+        units.define("week", 604_800.0, T, NotAllowed, "week");
+        units.define("seconds", 1.0, T, NotAllowed, "second");
+        units.define("minutes", 60.0, T, NotAllowed, "minute");
+        units.define("hours", 3600.0, T, NotAllowed, "hour");
+        units.define("days", 86_400.0, T, NotAllowed, "day");
+        units.define("weeks", 604_800.0, T, NotAllowed, "week");
+        units.define("yr", 31_557_600.0, T, NotAllowed, "Julian year");
+
+        units.define("inch", 0.0254, L, NotAllowed, "inch");
+        units.define("ft", 0.3048, L, NotAllowed, "foot");
+        units.define("yd", 0.9144, L, NotAllowed, "yard");
+        units.define("mi", 1609.344, L, NotAllowed, "mile");
+        units.define("nmi", 1852.0, L, NotAllowed, "nautical mile");
+        units.define("acre", 4_046.856_422_4, area, NotAllowed, "acre");
+        units.define("gal", 3.785_411_784e-3, volume, NotAllowed, "US gallon");
+        units.define("oz", 0.028_349_523_125, M, NotAllowed, "ounce");
+        units.define("lb", 0.453_592_37, M, NotAllowed, "pound");
+        units.define("mph", 0.447_04, speed, NotAllowed, "mile per hour");
+        units.define("kn", 1852.0 / 3600.0, speed, NotAllowed, "knot");
+
+        units
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(symbol: &str) -> Unit {
+        Unit::from(Units::default().get(symbol).unwrap())
+    }
+
+    fn quantity(value: f64, symbol: &str) -> Quantity {
+        Quantity::new(value, unit(symbol))
+    }
+
+    #[test]
+    fn test_lookup() {
+        let units = Units::default();
+
+        assert_eq!(Some(1000.0), units.get("km").map(|u| u.scale));
+        assert_eq!(Some(1.0), units.get("kg").map(|u| u.scale));
+        assert_eq!(Some(1e-6), units.get("µs").map(|u| u.scale));
+        assert_eq!(Some(10.0), units.get("dam").map(|u| u.scale));
+        assert_eq!(Some(60.0), units.get("min").map(|u| u.scale));
+        assert_eq!(None, units.get("kmin"));
+        assert_eq!(None, units.get("kft"));
+        assert_eq!(None, units.get("x"));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let speed = quantity(72.0, "km").div(&quantity(1.0, "h"));
+        assert_eq!("72 km/h", speed.to_string());
+        assert_eq!(
+            Some(quantity(20.0, "m").div(&quantity(1.0, "s"))),
+            speed.convert(&unit("m").div(&unit("s")))
+        );
+
+        assert_eq!(
+            Some(quantity(5.3, "km")),
+            quantity(5.0, "km").add(&quantity(300.0, "m"))
+        );
+        assert_eq!(None, quantity(1.0, "m").add(&quantity(1.0, "s")));
+
+        let area = quantity(4.0, "m").mul(&quantity(4.0, "m"));
+        assert_eq!("16 m^2", area.to_string());
+        assert_eq!(Some(quantity(4.0, "m")), area.pow(0.5));
+        assert_eq!(None, quantity(2.0, "m").pow(0.5));
+        assert_eq!(Dimension::NONE, area.div(&area).dimension());
+    }
+
+    #[test]
+    fn test_display() {
+        let force = unit("kg").mul(&unit("m")).div(&unit("s").pow(2));
+        assert_eq!("kg*m/s^2", force.to_string());
+        assert_eq!("m*kg/s^2", force.dimension().to_string());
+        assert_eq!("1/s", unit("Hz").dimension().to_string());
+        assert_eq!(
+            "m^2/(s*K)",
+            unit("m").pow(2).div(&unit("s").mul(&unit("K"))).to_string()
+        );
+        assert_eq!("1", Unit::one().to_string());
+    }
+
+    #[test]
+    fn test_currencies() {
+        let mut units = Units::default();
+        units.set_rates(&Rates::from_csv("date,2026-10-16\nbase,EUR\nUSD,1.25").unwrap());
+        let money =
+            |value: f64, code: &str| Quantity::new(value, Unit::from(units.get(code).unwrap()));
+
+        assert_eq!(Some("2026-10-16"), units.rates_date());
+        assert_eq!(
+            Some(money(80.0, "EUR")),
+            money(100.0, "USD").convert(&Unit::from(units.get("EUR").unwrap()))
+        );
+        assert_eq!(
+            Some(money(3.0, "USD")),
+            money(1.0, "USD").add(&money(2.0, "USD"))
+        );
+        assert_eq!(None, money(1.0, "USD").add(&money(1.0, "EUR")));
+        assert_eq!(None, units.get("kUSD"));
+
+        units.set_rates(&Rates::from_csv("date,2026-10-17\nbase,USD\nJPY,150").unwrap());
+        assert_eq!(None, units.get("EUR"));
+        assert_eq!("¤", units.get("JPY").unwrap().dimension.to_string());
+    }
+}
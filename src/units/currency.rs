@@ -0,0 +1,329 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("cannot read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid rates file, line {line}: {message}")]
+    Invalid { line: usize, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A table of exchange rates as of some date: how many units of each
+/// currency one unit of the `base` currency buys. The base has a rate of 1.
+///
+/// Rates are read from a local file, as JSON:
+///
+/// ```text
+/// {"base": "EUR", "date": "2026-10-16", "rates": {"USD": 1.0812, "JPY": 162.3}}
+/// ```
+///
+/// or as CSV, with `#` comments:
+///
+/// ```text
+/// date,2026-10-16
+/// base,EUR
+/// USD,1.0812
+/// JPY,162.3
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rates {
+    pub base: String,
+    pub date: String,
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl Rates {
+    /// Reads the rates from `path`, as JSON if its extension is `.json` and
+    /// as CSV otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&text)
+        } else {
+            Self::from_csv(&text)
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let mut reader = JsonReader::new(text);
+        let (mut base, mut date, mut rates) = (None, None, BTreeMap::new());
+
+        reader.eat('{')?;
+        loop {
+            let key = reader.string()?;
+            let line = reader.line;
+            reader.eat(':')?;
+            match key.as_str() {
+                "base" => base = Some((reader.string()?, line)),
+                "date" => date = Some(reader.string()?),
+                "rates" => {
+                    reader.eat('{')?;
+                    loop {
+                        let (code, line) = (reader.string()?, reader.line);
+                        reader.eat(':')?;
+                        rates.insert(code, (reader.number()?, line));
+                        if !reader.eat_separator('}')? {
+                            break;
+                        }
+                    }
+                }
+                _ => {
+                    return Err(reader.invalid(format!("unknown key \"{key}\"")));
+                }
+            }
+            if !reader.eat_separator('}')? {
+                break;
+            }
+        }
+        reader.end()?;
+
+        Self::new(base, date, rates, reader.line)
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let (mut base, mut date, mut rates) = (None, None, BTreeMap::new());
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(',') else {
+                return Err(Error::Invalid {
+                    line: line_number,
+                    message: "expected two comma-separated fields".to_owned(),
+                });
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "currency" => {} // A header line.
+                "date" => date = Some(value.to_owned()),
+                "base" => base = Some((value.to_owned(), line_number)),
+                code => {
+                    let rate = value.parse().map_err(|_| Error::Invalid {
+                        line: line_number,
+                        message: format!("'{value}' is not a number"),
+                    })?;
+                    rates.insert(code.to_owned(), (rate, line_number));
+                }
+            }
+        }
+
+        Self::new(base, date, rates, text.lines().count())
+    }
+
+    /// Checks the fields read from a file, with the line each was on.
+    fn new(
+        base: Option<(String, usize)>,
+        date: Option<String>,
+        rates: BTreeMap<String, (f64, usize)>,
+        last_line: usize,
+    ) -> Result<Self> {
+        let missing = |field: &str| Error::Invalid {
+            line: last_line,
+            message: format!("no {field} is given"),
+        };
+        let (base, base_line) = base.ok_or_else(|| missing("base currency"))?;
+        let date = date.ok_or_else(|| missing("date"))?;
+
+        let mut checked = BTreeMap::new();
+        for (code, (rate, line)) in rates.into_iter().chain([(base.clone(), (1.0, base_line))]) {
+            if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(Error::Invalid {
+                    line,
+                    message: format!("'{code}' is not a currency code"),
+                });
+            }
+            if !(rate.is_finite() && rate > 0.0) {
+                return Err(Error::Invalid {
+                    line,
+                    message: format!("the rate for {code} must be a positive number"),
+                });
+            }
+            checked.insert(code, rate);
+        }
+
+        Ok(Self {
+            base,
+            date,
+            rates: checked,
+        })
+    }
+}
+
+/// Reads the little JSON that a rates file needs: objects, strings and
+/// numbers.
+struct JsonReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn invalid(&self, message: impl Into<String>) -> Error {
+        Error::Invalid {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.peek().filter(|c| c.is_whitespace()) {
+            if c == '\n' {
+                self.line += 1;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().copied()
+    }
+
+    fn eat(&mut self, expected: char) -> Result<()> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            Some(c) => Err(self.invalid(format!("expected '{expected}', found '{c}'"))),
+            None => Err(self.invalid(format!("expected '{expected}', found the end"))),
+        }
+    }
+
+    /// Eats the ',' between members, and returns whether another follows,
+    /// or eats the closing `close`.
+    fn eat_separator(&mut self, close: char) -> Result<bool> {
+        if self.peek() == Some(',') {
+            self.chars.next();
+            Ok(true)
+        } else {
+            self.eat(close)?;
+            Ok(false)
+        }
+    }
+
+    fn end(&mut self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.invalid(format!("unexpected '{c}' after the end"))),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.eat('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.chars.next() {
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('u') => {
+                            let hex = (&mut self.chars).take(4).collect::<String>();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.invalid("invalid \\u escape"))?
+                        }
+                        _ => return Err(self.invalid("invalid escape in a string")),
+                    };
+                    s.push(c);
+                }
+                Some('\n') | None => return Err(self.invalid("unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        self.skip_whitespace();
+        let mut s = String::new();
+        while let Some(&c) = self
+            .chars
+            .peek()
+            .filter(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            s.push(c);
+            self.chars.next();
+        }
+        s.parse()
+            .map_err(|_| self.invalid(format!("expected a number, found '{s}'")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let rates = Rates::from_json(
+            r#"{
+                "base": "EUR",
+                "date": "2026-10-16",
+                "rates": {"USD": 1.08, "JPY": 1.62e2}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!("EUR", rates.base);
+        assert_eq!("2026-10-16", rates.date);
+        assert_eq!(Some(&1.0), rates.rates.get("EUR"));
+        assert_eq!(Some(&1.08), rates.rates.get("USD"));
+        assert_eq!(Some(&162.0), rates.rates.get("JPY"));
+
+        match Rates::from_json("{\"base\": \"EUR\",\n \"rates\": {\"USD\": -1}}") {
+            Err(Error::Invalid { line, .. }) => assert_eq!(2, line),
+            res => panic!("expected an invalid rates error, got {res:?}"),
+        }
+        assert!(Rates::from_json("{\"base\": \"EUR\", \"date\": \"today\"} x").is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        let rates = Rates::from_csv(
+            "# ECB reference rates\ncurrency,rate\ndate,2026-10-16\nbase,EUR\nUSD, 1.08\n",
+        )
+        .unwrap();
+
+        assert_eq!(Some(&1.08), rates.rates.get("USD"));
+        assert_eq!(2, rates.rates.len());
+
+        match Rates::from_csv("base,EUR\ndate,2026-10-16\nUSD,one") {
+            Err(Error::Invalid { line, .. }) => assert_eq!(3, line),
+            res => panic!("expected an invalid rates error, got {res:?}"),
+        }
+        assert!(Rates::from_csv("base,EUR\nUSD,1.08").is_err());
+        assert!(Rates::from_csv("date,2026-10-16\nbase,EUR\nUS$,1.08").is_err());
+    }
+}
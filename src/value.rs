@@ -0,0 +1,236 @@
+use std::{fmt, rc::Rc};
+
+use crate::{
+    ast::Ast,
+    datetime::{Date, DateTime},
+    environment::Scope,
+    number::{complex::Complex, decimal::Decimal, rational::Rational, Numeric},
+    units::Quantity,
+};
+
+/// The result of evaluating an expression, with numbers of type `N`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<N = f64> {
+    Number(N),
+    /// A result that `N` cannot hold exactly, such as that of a built-in
+    /// function when `N` is an exact type.
+    Float(f64),
+    /// A complex number with a non-zero imaginary part. Complex results with
+    /// none are real numbers instead.
+    Complex(Complex),
+    /// A number with a unit. Quantities whose units cancel out are plain
+    /// numbers instead.
+    Quantity(Quantity),
+    Bool(bool),
+    /// A day, as in `@2026-10-18`.
+    Date(Date),
+    /// An instant, as in `@2026-10-18T14:30:00+02:00`.
+    DateTime(DateTime),
+    /// A function defined in an expression, as in `f(x) = x^2` or
+    /// `x -> x^2`.
+    Function(Rc<UserFunction<N>>),
+    /// A list of values, such as the result of `map`.
+    List(Vec<Value<N>>),
+    /// A list of rows of the same length, as in `[[1, 2], [3, 4]]`.
+    Matrix(Matrix<N>),
+}
+
+/// A matrix with at least one row and one column, its elements stored row
+/// by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<N = f64> {
+    rows: usize,
+    cols: usize,
+    elements: Vec<Value<N>>,
+}
+
+impl<N> Matrix<N> {
+    /// A matrix of `rows` rows and `cols` columns from its elements, row by
+    /// row.
+    ///
+    /// # Panics
+    ///
+    /// If there are no rows or columns, or not `rows * cols` elements.
+    pub fn new(rows: usize, cols: usize, elements: Vec<Value<N>>) -> Self {
+        assert!(rows > 0 && cols > 0, "a matrix has rows and columns");
+        assert_eq!(rows * cols, elements.len(), "a matrix is full");
+        Self {
+            rows,
+            cols,
+            elements,
+        }
+    }
+
+    /// A matrix from its rows, or `None` if there are none or they are not
+    /// all of the same, non-zero, length.
+    pub fn from_rows(rows: Vec<Vec<Value<N>>>) -> Option<Self> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        Some(Self::new(
+            rows.len(),
+            cols,
+            rows.into_iter().flatten().collect(),
+        ))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The element in row `row` and column `col`, counting from zero.
+    pub fn get(&self, row: usize, col: usize) -> &Value<N> {
+        &self.elements[row * self.cols + col]
+    }
+
+    /// The elements, row by row.
+    pub fn elements(&self) -> &[Value<N>] {
+        &self.elements
+    }
+
+    pub fn iter_rows(&self) -> std::slice::Chunks<'_, Value<N>> {
+        self.elements.chunks(self.cols)
+    }
+}
+
+impl<N: Clone> Matrix<N> {
+    /// The matrix with its rows as columns.
+    pub fn transpose(&self) -> Self {
+        let elements = (0..self.cols)
+            .flat_map(|col| (0..self.rows).map(move |row| (row, col)))
+            .map(|(row, col)| self.get(row, col).clone())
+            .collect();
+        Self::new(self.cols, self.rows, elements)
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Matrix<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, row) in self.iter_rows().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[")?;
+            for (j, value) in row.iter().enumerate() {
+                if j > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{value}")?;
+            }
+            write!(f, "]")?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// A function defined by the user, which evaluates `body` with its
+/// parameters bound to the arguments of a call.
+#[derive(Debug)]
+pub struct UserFunction<N = f64> {
+    /// The name it was defined under, or `None` for a lambda.
+    pub name: Option<String>,
+    pub params: Vec<String>,
+    pub body: Rc<Ast<N>>,
+    /// The scope of the call a lambda was created in, and of the calls
+    /// enclosing that, which it keeps once they have returned, as `n` in
+    /// `adder(n) = x -> x + n`.
+    pub captured: Option<Rc<Scope<N>>>,
+}
+
+impl<N> PartialEq for UserFunction<N> {
+    /// Functions are equal only to themselves, since telling whether two
+    /// bodies compute the same thing is out of reach.
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl<N> fmt::Display for UserFunction<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name.as_deref().unwrap_or("lambda");
+        write!(f, "{name}({})", self.params.join(", "))
+    }
+}
+
+impl<N: Numeric> Value<N> {
+    /// The value as an `f64`, rounding it if it is exact, or `None` if it
+    /// is not a real number.
+    pub fn to_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Number(ref n) => Some(n.to_f64()),
+            Value::Float(x) => Some(x),
+            Value::Complex(_)
+            | Value::Quantity(_)
+            | Value::Bool(_)
+            | Value::Date(_)
+            | Value::DateTime(_)
+            | Value::Function(_)
+            | Value::List(_)
+            | Value::Matrix(_) => None,
+        }
+    }
+
+    /// The value as a complex number, rounding it if it is exact, or `None`
+    /// if it has a unit or is not a number.
+    pub fn to_complex(&self) -> Option<Complex> {
+        match *self {
+            Value::Complex(z) => Some(z),
+            _ => self.to_f64().map(Complex::from),
+        }
+    }
+
+    /// The value as a quantity, rounding it if it is exact, or `None` if it
+    /// is complex or not a number. Plain numbers are quantities without a unit.
+    pub fn to_quantity(&self) -> Option<Quantity> {
+        match *self {
+            Value::Quantity(ref q) => Some(q.clone()),
+            _ => self.to_f64().map(Quantity::from),
+        }
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value<$t> {
+                fn from(n: $t) -> Self {
+                    Value::Number(n)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(f64, f32, i64, i128, Rational, Decimal);
+
+impl<N: fmt::Display> fmt::Display for Value<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Value::Number(ref n) => write!(f, "{n}"),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::Complex(z) => write!(f, "{z}"),
+            Value::Quantity(ref q) => write!(f, "{q}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Date(date) => write!(f, "{date}"),
+            Value::DateTime(datetime) => write!(f, "{datetime}"),
+            Value::Function(ref function) => write!(f, "{function}"),
+            Value::List(ref values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Matrix(ref matrix) => write!(f, "{matrix}"),
+        }
+    }
+}